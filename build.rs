@@ -1,184 +1,93 @@
-use regex::Regex;
-use sha2::{Digest, Sha256};
-use std::{
-    env,
-    fs::{self, File},
-    io::{BufRead, BufReader, LineWriter, copy, prelude::*},
-    path::Path,
-    process::Command,
-    str,
-};
+use std::{env, fs, path::Path};
 
-// Adopted from https://github.com/bbqsrc/eventlog/blob/master/build.rs
-
-// This regex grabs all MC-generated #define statements and for each it
-// captures 3 groups: name, cast, value. The "cast" group is optional.
-// i.e. "#define SOMETHING   ((DWORD)0x1200L)" -> ("SOMETHING", "DWORD", 0x1200)
-const REGEX: &str = r"^#define (\S+)\s+\(?(\([[:alpha:]]+\))?\s*(0x[[:xdigit:]]+)";
+#[path = "build/message_table.rs"]
+mod message_table;
 
-const INPUT_FILE: &str = "res/eventmsgs.mc";
-const TMPL_FILE: &str = "res/eventmsgs.mc.tmpl";
-const GENERATED_FILE: &str = "res/eventmsgs.rs";
-const HEADER_FILE: &str = "res/eventmsgs.h";
-const LIB_FILE: &str = "res/eventmsgs.lib";
+use message_table::{BUILTIN_CATEGORIES, DEFAULT_LANGUAGE, DEFAULT_LANGUAGE_ID, Language};
 
-const MC_BIN: &str = "mc.exe";
-const MC_ARGS: &[&str] = &["-U", "-h", "res", "-r", "res", INPUT_FILE];
+// Adopted from https://github.com/bbqsrc/eventlog/blob/master/build.rs
 
-const RC_BIN: &str = "rc.exe";
-const RC_ARGS: &[&str] = &["/v", "/fo", "res/eventmsgs.lib", "res/eventmsgs.rc"];
+// The message table is configured with these environment variables:
+//
+// - TRACING_EVENTLOG_CATEGORIES: comma separated English category names
+// - TRACING_EVENTLOG_LANGUAGES: comma separated `Name=LangId` pairs of additional languages,
+//   e.g. "German=0x407, Japanese=0x411"
+// - TRACING_EVENTLOG_CATEGORIES_<NAME>: category names in the language `Name`, in the same order
+//   as the English ones and followed by the built-in categories
+// - TRACING_EVENTLOG_MESSAGES, TRACING_EVENTLOG_MESSAGES_<NAME>: semicolon separated
+//   `SYMBOL=text` pairs overriding the text of messages, e.g. "MSG_PANIC=Panic: %1"
+//
+// Anything missing for a language falls back to the English text.
+//
+// The message table is compiled into OUT_DIR: eventmsgs.rs holds the constants included by
+// src/eventmsgs.rs and eventmsgs.lib the resource linked into Windows binaries. eventmsgs.mc is
+// the filled in template, for inspecting the output or compiling it with mc.exe.
 
-const FUNC_TEXT: &str = "
-#[allow(unused_variables)]
-pub fn get_category(category: String) -> u16 {
-{TEXT}
+const TMPL_FILE: &str = "res/eventmsgs.mc.tmpl";
+const INPUT_FILE: &str = "eventmsgs.mc";
+const GENERATED_FILE: &str = "eventmsgs.rs";
+const LIB_FILE: &str = "eventmsgs.lib";
+
+/// Reads a configuration variable, rebuilding the message table when it changes.
+fn env_var(name: &str) -> Option<String> {
+    println!("cargo:rerun-if-env-changed={name}");
+    env::var(name).ok()
 }
-";
 
-const MATCH_TEXT: &str = "
-    match category.trim().to_lowercase().as_ref() {
-        {TEXT}
-        _ => 0,
-    }
-";
-
-fn run_tool(program: &str, args: &[&str]) -> Result<(), ()> {
-    let mut command = Command::new(program);
-    match command.args(args).output() {
-        Ok(out) => {
-            println!("{:?}", str::from_utf8(&out.stderr).unwrap());
-            println!("{:?}", str::from_utf8(&out.stdout).unwrap());
-            Ok(())
-        }
-        Err(err) => {
-            println!("ERROR: Failed to run command: {}, error: {}", program, err);
-            Err(())
-        }
-    }
+fn split_list(list: &str, separator: char) -> Vec<String> {
+    list.split(separator)
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty())
+        .collect()
 }
 
-fn gen_rust(origin_hash: &str, category_list: Vec<&str>) {
-    let re = Regex::new(REGEX).unwrap();
-
-    let file_out = File::create(GENERATED_FILE).unwrap();
-    let mut writer = LineWriter::new(file_out);
-
-    writer
-        .write_all(
-            format!(
-                "// Auto-generated from origin with SHA256 {}.\n",
-                origin_hash
-            )
-            .as_bytes(),
-        )
-        .unwrap();
-    writer
-        .write_all(
-            format!(
-                "pub(crate) const CATEGORY_COUNT: u32 = {};\n\n",
-                category_list.len(),
-            )
-            .as_bytes(),
-        )
-        .unwrap();
-
-    let file_in = File::open("res/eventmsgs.h").unwrap();
-    for line_res in BufReader::new(file_in).lines() {
-        let line = line_res.unwrap();
-        if let Some(x) = re.captures(&line) {
-            let datatype = match x.get(2).map(|v| v.as_str()) {
-                Some("(WORD)") => "u16",
-                Some("(DWORD)") => "u32",
-                _ => "u32",
-            };
-            writer
-                .write_all(format!("pub const {}: {datatype} = {};\n", &x[1], &x[3]).as_bytes())
-                .unwrap();
-        }
-    }
-
-    let func_body = if category_list.is_empty() {
-        "0".to_owned()
+fn get_language(name: &str, id: u16) -> Language {
+    let var_suffix = if name == DEFAULT_LANGUAGE {
+        "".to_owned()
     } else {
-        let match_cases = category_list
-            .iter()
-            .map(|c| {
-                format!(
-                    "\"\\\"{}\\\"\" => {},",
-                    c.to_lowercase(),
-                    get_category_const(c)
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n        ");
-        MATCH_TEXT.replace("{TEXT}", &match_cases)
+        format!("_{}", name.to_uppercase())
     };
-
-    let category_func_text = FUNC_TEXT.replace("{TEXT}", &func_body);
-    writer.write_all(category_func_text.as_bytes()).unwrap();
-}
-
-fn file_hash(f: &str) -> String {
-    let mut file = File::open(f).unwrap();
-    let mut hasher = Sha256::new();
-    let _count = copy(&mut file, &mut hasher).unwrap();
-    let formatted = format!("{:x}", hasher.finalize());
-    println!("file={}, hash={}", f, formatted);
-    formatted
-}
-
-fn file_contains(f: &str, needle: &str) -> bool {
-    match File::open(f) {
-        Err(_) => false,
-        Ok(file) => {
-            for line in BufReader::new(file).lines() {
-                if line.unwrap().contains(needle) {
-                    println!("file={} contains {}", f, needle);
-                    return true;
-                }
-            }
-            println!("file={} does not contain {}", f, needle);
-            false
-        }
-    }
-}
-
-fn delete_if_exists(path: impl AsRef<Path>) {
-    let path = path.as_ref();
-    if path.exists() {
-        fs::remove_file(path).unwrap();
+    let categories = env_var(&format!("TRACING_EVENTLOG_CATEGORIES{var_suffix}"))
+        .map(|list| split_list(&list, ','))
+        .unwrap_or_default();
+    let messages = env_var(&format!("TRACING_EVENTLOG_MESSAGES{var_suffix}"))
+        .map(|list| {
+            split_list(&list, ';')
+                .iter()
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(symbol, text)| (symbol.trim().to_owned(), text.trim().to_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    Language {
+        name: name.to_owned(),
+        id,
+        categories,
+        messages,
     }
 }
 
-fn error_if_not_found(path: impl AsRef<Path>) {
-    let path = path.as_ref();
-    if !path.exists() {
-        panic!("failed to generate {:?}", path);
+fn get_languages() -> Vec<Language> {
+    let mut languages = vec![get_language(DEFAULT_LANGUAGE, DEFAULT_LANGUAGE_ID)];
+    if let Some(list) = env_var("TRACING_EVENTLOG_LANGUAGES") {
+        for entry in split_list(&list, ',') {
+            let (name, id) = entry
+                .split_once('=')
+                .unwrap_or_else(|| panic!("expected Name=LangId, found {entry:?}"));
+            let id = id.trim().trim_start_matches("0x");
+            let id = u16::from_str_radix(id, 16)
+                .unwrap_or_else(|_| panic!("invalid language id in {entry:?}"));
+            languages.push(get_language(name.trim(), id));
+        }
     }
+    languages
 }
 
-fn get_category_const(category: &str) -> String {
-    let category_name = category.replace(' ', "_").to_uppercase();
-    format!("{category_name}_CATEGORY")
-}
-
-fn gen_category_text(name: &str, id: usize) -> String {
-    let name = name.trim();
-    let category_name = get_category_const(name);
-    format!("MessageId={id:#X}\nSymbolicName={category_name}\nLanguage=English\n{name}\n.\n")
-}
-
-#[cfg(windows)]
-fn get_tool_path(name: &str) -> String {
-    embed_resource::find_windows_sdk_tool(name)
-        .unwrap()
-        .to_string_lossy()
-        .to_string()
-}
-
-#[cfg(not(windows))]
-fn get_tool_path(name: &str) -> String {
-    name.to_owned()
+fn write_utf16(path: &Path, contents: &str) {
+    // mc.exe only reads non-ANSI text from UTF-16 files
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(contents.encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(path, bytes).unwrap();
 }
 
 fn main() {
@@ -186,51 +95,39 @@ fn main() {
         println!("Env[{}]={}", key, value);
     }
 
-    let (categories, category_list) = match option_env!("TRACING_EVENTLOG_CATEGORIES") {
-        Some(categories) => {
-            let category_list = categories.split(',').map(|c| c.trim()).collect::<Vec<_>>();
-            let category_text = category_list
-                .iter()
-                .enumerate()
-                .map(|(i, category)| gen_category_text(category, i + 1))
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            (
-                format!("; // Event categories\n\nMessageIdTypedef=WORD\n\n{category_text}"),
-                category_list,
-            )
-        }
-        None => ("".to_owned(), vec![]),
-    };
-    let file_contents = fs::read_to_string(TMPL_FILE).unwrap();
-    let new_contents = file_contents.replace("{CATEGORIES}", &categories);
-    fs::write(INPUT_FILE, new_contents).unwrap();
-
-    if !cfg!(windows) {
-        return;
-    }
-
-    let origin_hash = file_hash(TMPL_FILE);
-    if !file_contains(GENERATED_FILE, &origin_hash) {
-        println!(
-            "Generating {} from {} with hash {}",
-            GENERATED_FILE, INPUT_FILE, origin_hash
-        );
-        let mc_cmd = get_tool_path(MC_BIN);
-        let rc_cmd = get_tool_path(RC_BIN);
-
-        delete_if_exists(HEADER_FILE);
-        run_tool(&mc_cmd, MC_ARGS).unwrap();
-        error_if_not_found(HEADER_FILE);
-
-        delete_if_exists(LIB_FILE);
-        run_tool(&rc_cmd, RC_ARGS).unwrap();
-        error_if_not_found(LIB_FILE);
-        gen_rust(&origin_hash, category_list);
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=build/message_table.rs");
+    println!("cargo:rerun-if-changed={TMPL_FILE}");
+
+    let languages = get_languages();
+    let mut category_list = languages[0]
+        .categories
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    category_list.extend(BUILTIN_CATEGORIES);
+
+    let template = fs::read_to_string(TMPL_FILE).unwrap();
+    let source = message_table::message_source(&template, &category_list, &languages);
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+    write_utf16(&out_dir.join(INPUT_FILE), &source);
+
+    let messages = message_table::parse(&source);
+    fs::write(
+        out_dir.join(GENERATED_FILE),
+        message_table::rust_source(&messages),
+    )
+    .unwrap();
+    fs::write(
+        out_dir.join(LIB_FILE),
+        message_table::resource_file(&messages, &languages),
+    )
+    .unwrap();
+
+    // The linker takes the compiled resource like a library
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        println!("cargo:rustc-link-search=native={}", out_dir.display());
+        println!("cargo:rustc-link-lib=dylib=eventmsgs");
     }
-
-    let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    println!("cargo:rustc-link-search=native={}/res", dir);
-    println!("cargo:rustc-link-lib=dylib=eventmsgs");
 }
@@ -0,0 +1,332 @@
+// Compiles the message table of the event source from res/eventmsgs.mc.tmpl, doing the work of
+// mc.exe and rc.exe so that building doesn't need the Windows SDK. It is included by build.rs
+// and by the unit tests, so it only uses std.
+
+use std::collections::HashMap;
+
+pub const DEFAULT_LANGUAGE: &str = "English";
+pub const DEFAULT_LANGUAGE_ID: u16 = 0x409;
+const DEFAULT_MESSAGE_TEXT: &str = "%1";
+
+// Categories used by the crate itself, numbered after the configured ones
+pub const BUILTIN_CATEGORIES: &[&str] = &["Panic", "Startup", "Shutdown"];
+
+// Resource type of message tables
+const RT_MESSAGETABLE: u16 = 11;
+// MOVEABLE | PURE, as set by rc.exe
+const MEMORY_FLAGS: u16 = 0x30;
+// The text of a message entry is UTF-16
+const UNICODE_ENTRY: u16 = 1;
+
+const FUNC_TEXT: &str = "
+#[allow(unused_variables)]
+pub fn get_category(category: String) -> u16 {
+{TEXT}
+}
+";
+
+const MATCH_TEXT: &str = "
+    match category.trim().to_lowercase().as_ref() {
+        {TEXT}
+        _ => 0,
+    }
+";
+
+pub struct Language {
+    pub name: String,
+    pub id: u16,
+    /// Localized names of the configured categories followed by the built-in ones.
+    pub categories: Vec<String>,
+    /// Message texts by symbolic name.
+    pub messages: HashMap<String, String>,
+}
+
+impl Language {
+    fn names_text(&self) -> String {
+        format!(
+            "LanguageNames=({}={:#x}:MSG{:05X})",
+            self.name, self.id, self.id
+        )
+    }
+
+    fn block(&self, text: &str) -> String {
+        format!("Language={}\n{text}\n.\n", self.name)
+    }
+}
+
+/// A category or message of the compiled table.
+#[derive(Debug, PartialEq)]
+pub struct Message {
+    pub symbol: String,
+    pub id: u32,
+    /// Whether the ID is declared as a WORD, as categories are.
+    pub word: bool,
+    /// The text of the message by language name.
+    pub texts: Vec<(String, String)>,
+}
+
+fn category_const(category: &str) -> String {
+    let category_name = category.replace(' ', "_").to_uppercase();
+    format!("{category_name}_CATEGORY")
+}
+
+fn category_text(name: &str, id: usize, languages: &[Language]) -> String {
+    let category_name = category_const(name);
+    let blocks = languages
+        .iter()
+        .map(|language| {
+            let localized = language.categories.get(id - 1).map_or(name, |c| c.as_str());
+            language.block(localized)
+        })
+        .collect::<String>();
+    format!("MessageId={id:#X}\nSymbolicName={category_name}\n{blocks}")
+}
+
+fn message_texts(symbol: &str, languages: &[Language]) -> String {
+    languages
+        .iter()
+        .map(|language| {
+            let text = language
+                .messages
+                .get(symbol)
+                .or_else(|| languages[0].messages.get(symbol))
+                .map_or(DEFAULT_MESSAGE_TEXT, |text| text.as_str());
+            language.block(text)
+        })
+        .collect()
+}
+
+/// Fills in the `{LANGUAGE_NAMES}`, `{CATEGORIES}` and `{TEXT:<symbol>}` placeholders of the
+/// template. The first language is the default one, whose categories and messages are used
+/// where another language has none.
+pub fn message_source(template: &str, categories: &[&str], languages: &[Language]) -> String {
+    let category_text = categories
+        .iter()
+        .enumerate()
+        .map(|(i, category)| category_text(category.trim(), i + 1, languages))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let categories = format!("; // Event categories\n\nMessageIdTypedef=WORD\n\n{category_text}");
+    let language_names = languages
+        .iter()
+        .map(Language::names_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    template
+        .replace("{LANGUAGE_NAMES}", &language_names)
+        .replace("{CATEGORIES}", &categories)
+        .lines()
+        .map(|line| match line.strip_prefix("{TEXT:") {
+            Some(symbol) => message_texts(symbol.trim_end_matches('}'), languages),
+            None => format!("{line}\n"),
+        })
+        .collect()
+}
+
+fn parse_id(value: &str) -> u32 {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .unwrap_or_else(|_| panic!("invalid MessageId {value:?}"))
+}
+
+fn severity_bits(severity: &str) -> u32 {
+    let severity = match severity {
+        "Success" => 0,
+        "Informational" => 1,
+        "Warning" => 2,
+        "Error" => 3,
+        _ => panic!("unknown Severity {severity:?}"),
+    };
+    severity << 30
+}
+
+/// Parses the statements of the message compiler's syntax that [`message_source`] produces.
+pub fn parse(source: &str) -> Vec<Message> {
+    let mut messages = Vec::<Message>::new();
+    let mut word = false;
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end();
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if line.starts_with(';') {
+            continue;
+        }
+        let message = messages.last_mut();
+        match (key, message) {
+            ("MessageIdTypedef", _) => word = value == "WORD",
+            ("MessageId", _) => messages.push(Message {
+                symbol: String::new(),
+                id: parse_id(value),
+                word,
+                texts: Vec::new(),
+            }),
+            ("Severity", Some(message)) => message.id |= severity_bits(value),
+            ("SymbolicName", Some(message)) => message.symbol = value.to_owned(),
+            ("Language", Some(message)) => {
+                let text = lines
+                    .by_ref()
+                    .take_while(|line| *line != ".")
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                message.texts.push((value.to_owned(), text));
+            }
+            _ => {}
+        }
+    }
+    messages
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend(value.to_le_bytes());
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend(value.to_le_bytes());
+}
+
+fn pad_to_dword(bytes: &mut Vec<u8>) {
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
+
+// A MESSAGE_RESOURCE_ENTRY with the text terminated by CRLF and NUL
+fn message_entry(text: &str) -> Vec<u8> {
+    let mut text = text
+        .lines()
+        .flat_map(|line| line.encode_utf16().chain("\r\n".encode_utf16()))
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    push_u16(&mut text, 0);
+    pad_to_dword(&mut text);
+    let mut entry = Vec::with_capacity(text.len() + 4);
+    push_u16(&mut entry, (text.len() + 4) as u16);
+    push_u16(&mut entry, UNICODE_ENTRY);
+    entry.extend(text);
+    entry
+}
+
+/// The MESSAGE_RESOURCE_DATA of one language, with a block per run of consecutive IDs.
+pub fn message_table(messages: &[Message], language: &str) -> Vec<u8> {
+    let mut entries = messages
+        .iter()
+        .filter_map(|message| {
+            let (_, text) = message.texts.iter().find(|(name, _)| name == language)?;
+            Some((message.id, message_entry(text)))
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|&(id, _)| id);
+
+    let mut blocks = Vec::<(u32, u32, usize)>::new();
+    for (i, &(id, _)) in entries.iter().enumerate() {
+        match blocks.last_mut() {
+            Some((_, high, _)) if high.checked_add(1) == Some(id) => *high = id,
+            _ => blocks.push((id, id, i)),
+        }
+    }
+
+    let mut offsets = Vec::with_capacity(entries.len());
+    let mut offset = 4 + 12 * blocks.len();
+    for (_, entry) in &entries {
+        offsets.push(offset);
+        offset += entry.len();
+    }
+
+    let mut table = Vec::with_capacity(offset);
+    push_u32(&mut table, blocks.len() as u32);
+    for &(low, high, first) in &blocks {
+        push_u32(&mut table, low);
+        push_u32(&mut table, high);
+        push_u32(&mut table, offsets[first] as u32);
+    }
+    for (_, entry) in entries {
+        table.extend(entry);
+    }
+    table
+}
+
+// A RESOURCEHEADER with numeric type and name
+fn resource_header(
+    bytes: &mut Vec<u8>,
+    data_size: u32,
+    (type_id, name_id): (u16, u16),
+    memory_flags: u16,
+    language: u16,
+) {
+    push_u32(bytes, data_size);
+    push_u32(bytes, 0x20);
+    push_u16(bytes, 0xFFFF);
+    push_u16(bytes, type_id);
+    push_u16(bytes, 0xFFFF);
+    push_u16(bytes, name_id);
+    push_u32(bytes, 0);
+    push_u16(bytes, memory_flags);
+    push_u16(bytes, language);
+    push_u32(bytes, 0);
+    push_u32(bytes, 0);
+}
+
+/// A compiled resource file with the message table of every language, as rc.exe writes it.
+pub fn resource_file(messages: &[Message], languages: &[Language]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    // rc.exe starts every file with an empty resource
+    resource_header(&mut bytes, 0, (0, 0), 0, 0);
+    for language in languages {
+        let table = message_table(messages, &language.name);
+        resource_header(
+            &mut bytes,
+            table.len() as u32,
+            (RT_MESSAGETABLE, 1),
+            MEMORY_FLAGS,
+            language.id,
+        );
+        bytes.extend(table);
+        pad_to_dword(&mut bytes);
+    }
+    bytes
+}
+
+/// Rust constants for the IDs of the messages and categories, and `get_category`, which looks
+/// up categories by their Debug formatted name in the default language.
+pub fn rust_source(messages: &[Message]) -> String {
+    let categories = messages
+        .iter()
+        .filter(|message| message.word)
+        .collect::<Vec<_>>();
+    let mut source = String::from("// Generated by build.rs from res/eventmsgs.mc.tmpl.\n");
+    source += &format!(
+        "pub(crate) const CATEGORY_COUNT: u32 = {};\n\n",
+        categories.len()
+    );
+    for message in messages {
+        let datatype = if message.word { "u16" } else { "u32" };
+        source += &format!(
+            "pub const {}: {datatype} = 0x{:08X};\n",
+            message.symbol, message.id
+        );
+    }
+
+    let func_body = if categories.is_empty() {
+        "0".to_owned()
+    } else {
+        let match_cases = categories
+            .iter()
+            .map(|category| {
+                let name = category.texts.first().map_or("", |(_, text)| text.as_str());
+                format!(
+                    "\"\\\"{}\\\"\" => {},",
+                    name.to_lowercase(),
+                    category.symbol
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n        ");
+        MATCH_TEXT.replace("{TEXT}", &match_cases)
+    };
+    source + &FUNC_TEXT.replace("{TEXT}", &func_body)
+}
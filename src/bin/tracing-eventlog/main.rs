@@ -0,0 +1,323 @@
+//! Registers, inspects and removes event sources without running the application that logs.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+    error::Error,
+    io::{self, Write},
+    process::ExitCode,
+};
+use tracing_eventlog::{
+    EventLogRegistry, LogSource, MessageFiles, RegistrationStatus, RegistryPlan, RemoveLog,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Registers an event source, repairing an existing registration.
+    Register {
+        #[command(flatten)]
+        source: RegisterArgs,
+        /// Print the changes a fresh registration makes instead of making them. Works offline
+        /// on any OS.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Removes an event source, and its custom log once no sources are left.
+    Deregister {
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(long, value_enum, default_value_t = RemoveLogArg::IfEmpty)]
+        remove_log: RemoveLogArg,
+        /// Print the changes removing a registered source makes instead of making them. Works
+        /// offline on any OS.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Shows how an event source is registered.
+    Status {
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+    /// Lists the event logs and their sources.
+    List {
+        /// Only list the sources of this log.
+        log: Option<String>,
+        #[arg(long)]
+        server: Option<String>,
+    },
+    /// Prints a .reg file registering the source.
+    ExportReg {
+        #[command(flatten)]
+        source: RegisterArgs,
+    },
+    /// Writes an event through a registered source.
+    TestEvent {
+        source: String,
+        #[arg(long)]
+        server: Option<String>,
+        #[arg(long, value_enum, default_value_t = LevelArg::Info)]
+        level: LevelArg,
+        #[arg(long, default_value = "Test event written by tracing-eventlog")]
+        message: String,
+    },
+}
+
+#[derive(Args)]
+struct SourceArgs {
+    /// Name of the event source.
+    source: String,
+    /// Custom log containing the source, instead of the Application log.
+    #[arg(long)]
+    log: Option<String>,
+    /// Computer whose registry is used, the local machine if not set.
+    #[arg(long)]
+    server: Option<String>,
+}
+
+impl SourceArgs {
+    fn log_source(&self) -> LogSource {
+        let source = match &self.log {
+            Some(log) => LogSource::custom(log, vec![self.source.as_str()]),
+            None => LogSource::application(&self.source),
+        };
+        match &self.server {
+            Some(server) => source.with_server(server),
+            None => source,
+        }
+    }
+}
+
+#[derive(Args)]
+struct RegisterArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Module containing the event messages, usually the application's executable; may be given
+    /// several times. Required, since this tool's own path is no use once it is moved or deleted.
+    #[arg(long = "message-file", required = true)]
+    message_files: Vec<String>,
+}
+
+impl RegisterArgs {
+    fn log_source(&self) -> LogSource {
+        let message_files = self
+            .message_files
+            .iter()
+            .fold(MessageFiles::new(), |files, file| {
+                files.event_message_file(file)
+            });
+        self.source.log_source().with_message_files(message_files)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RemoveLogArg {
+    IfEmpty,
+    Never,
+    Force,
+}
+
+impl From<RemoveLogArg> for RemoveLog {
+    fn from(arg: RemoveLogArg) -> Self {
+        match arg {
+            RemoveLogArg::IfEmpty => RemoveLog::IfEmpty,
+            RemoveLogArg::Never => RemoveLog::Never,
+            RemoveLogArg::Force => RemoveLog::Force,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LevelArg {
+    Error,
+    Warn,
+    Info,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, out: &mut dyn Write) -> Result<()> {
+    match command {
+        Command::Register { source, dry_run } => {
+            let source = source.log_source();
+            if dry_run {
+                return print_plan(out, &source.plan_offline()?);
+            }
+            require_windows()?;
+            write!(out, "{}", with_newline(source.register()?.to_string()))?;
+        }
+        Command::Deregister {
+            source,
+            remove_log,
+            dry_run,
+        } => {
+            let source = source.log_source().with_remove_log(remove_log.into());
+            if dry_run {
+                return print_plan(out, &source.plan_deregister_offline()?);
+            }
+            require_windows()?;
+            let report = source.deregister()?;
+            writeln!(
+                out,
+                "removed sources: {}",
+                report.removed_sources.join(", ")
+            )?;
+            if report.log_removed {
+                writeln!(out, "removed the log")?;
+            }
+            for warning in &report.warnings {
+                writeln!(out, "warning: {warning}")?;
+            }
+        }
+        Command::Status { source } => {
+            require_windows()?;
+            print_status(out, &source.source, &source.log_source().status()?)?;
+        }
+        Command::List { log, server } => list(out, log.as_deref(), server.as_deref())?,
+        Command::ExportReg { source } => write!(out, "{}", source.log_source().to_reg_file()?)?,
+        Command::TestEvent {
+            source,
+            server,
+            level,
+            message,
+        } => test_event(out, &source, server.as_deref(), level, &message)?,
+    }
+    Ok(())
+}
+
+fn with_newline(mut text: String) -> String {
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+fn print_plan(out: &mut dyn Write, plan: &RegistryPlan) -> Result<()> {
+    write!(out, "{}", with_newline(plan.to_string()))?;
+    Ok(())
+}
+
+fn print_status(out: &mut dyn Write, source: &str, status: &RegistrationStatus) -> Result<()> {
+    match &status.log {
+        Some(log) => writeln!(out, "{source}: registered in the '{log}' log")?,
+        None => writeln!(out, "{source}: not registered")?,
+    }
+    for file in &status.message_files {
+        let state = match file.exists {
+            Some(true) => "found",
+            Some(false) => "missing",
+            None => "not checked",
+        };
+        writeln!(out, "  {}: {} ({state})", file.value, file.path)?;
+    }
+    if let Some(count) = status.category_count {
+        writeln!(out, "  CategoryCount: {count}")?;
+    }
+    if let Some(types) = status.types_supported {
+        writeln!(out, "  TypesSupported: {types:#x}")?;
+    }
+    for problem in &status.problems {
+        writeln!(out, "  problem: {problem}")?;
+    }
+    Ok(())
+}
+
+fn require_windows() -> Result<()> {
+    if cfg!(windows) {
+        Ok(())
+    } else {
+        Err("this command needs the Windows registry; use --dry-run or export-reg instead".into())
+    }
+}
+
+#[cfg(windows)]
+fn list(out: &mut dyn Write, log: Option<&str>, server: Option<&str>) -> Result<()> {
+    use tracing_eventlog::{WindowsRegistry, list_logs, list_sources};
+
+    let registry = match server {
+        Some(server) => WindowsRegistry::remote(server),
+        None => WindowsRegistry::new(),
+    };
+    match log {
+        Some(log) => {
+            for source in list_sources(&registry, log)? {
+                writeln!(out, "{source}")?;
+            }
+        }
+        None => {
+            for entry in list_logs(&registry)? {
+                writeln!(out, "{}", entry.name)?;
+                for source in &entry.sources {
+                    writeln!(out, "  {source}")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn list(_out: &mut dyn Write, _log: Option<&str>, _server: Option<&str>) -> Result<()> {
+    require_windows()
+}
+
+#[cfg(windows)]
+fn test_event(
+    out: &mut dyn Write,
+    source: &str,
+    server: Option<&str>,
+    level: LevelArg,
+    message: &str,
+) -> Result<()> {
+    use tracing_eventlog::EventLogLayerBuilder;
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    let mut builder = EventLogLayerBuilder::new(source);
+    if let Some(server) = server {
+        builder = builder.server(server);
+    }
+    let layer = builder.compact::<Registry>()?;
+    let stats = layer.stats();
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || match level {
+        LevelArg::Error => tracing::error!("{message}"),
+        LevelArg::Warn => tracing::warn!("{message}"),
+        LevelArg::Info => tracing::info!("{message}"),
+    });
+
+    let snapshot = stats.snapshot();
+    if snapshot.written == 0 {
+        return Err(format!("the event was not written: {:?}", snapshot.failed).into());
+    }
+    writeln!(out, "wrote the event to '{source}'")?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn test_event(
+    _out: &mut dyn Write,
+    _source: &str,
+    _server: Option<&str>,
+    _level: LevelArg,
+    _message: &str,
+) -> Result<()> {
+    require_windows()
+}
+
+#[cfg(test)]
+#[path = "./main_test.rs"]
+mod main_test;
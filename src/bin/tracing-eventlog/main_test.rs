@@ -0,0 +1,115 @@
+use super::*;
+use clap::error::ErrorKind;
+
+const APPLICATION_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\Application";
+
+fn parse(args: &[&str]) -> Command {
+    Cli::try_parse_from([&["tracing-eventlog"], args].concat())
+        .unwrap()
+        .command
+}
+
+fn run_to_string(args: &[&str]) -> Result<String> {
+    let mut out = Vec::new();
+    run(parse(args), &mut out)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_register_requires_message_file() {
+    let err = Cli::try_parse_from(["tracing-eventlog", "register", "Acme"])
+        .err()
+        .unwrap();
+
+    assert_eq!(ErrorKind::MissingRequiredArgument, err.kind());
+}
+
+#[test]
+fn test_parse_register() {
+    let command = parse(&[
+        "register",
+        "Acme",
+        "--log",
+        "Acme Log",
+        "--message-file",
+        r"C:\Acme\acme.exe",
+        "--message-file",
+        r"C:\Acme\messages.dll",
+        "--dry-run",
+    ]);
+
+    let Command::Register { source, dry_run } = command else {
+        panic!("expected register");
+    };
+    assert!(dry_run);
+    assert_eq!("Acme", source.source.source);
+    assert_eq!(Some("Acme Log"), source.source.log.as_deref());
+    assert_eq!(
+        vec![r"C:\Acme\acme.exe", r"C:\Acme\messages.dll"],
+        source.message_files
+    );
+}
+
+#[test]
+fn test_parse_deregister() {
+    let command = parse(&["deregister", "Acme", "--remove-log", "force"]);
+
+    let Command::Deregister {
+        remove_log,
+        dry_run,
+        ..
+    } = command
+    else {
+        panic!("expected deregister");
+    };
+    assert!(matches!(remove_log, RemoveLogArg::Force));
+    assert!(!dry_run);
+}
+
+#[test]
+fn test_register_dry_run() {
+    let output = run_to_string(&[
+        "register",
+        "Acme",
+        "--message-file",
+        r"C:\Acme\acme.exe",
+        "--dry-run",
+    ])
+    .unwrap();
+
+    assert!(output.starts_with(&format!("create key {APPLICATION_KEY}\\Acme\n")));
+    assert!(output.contains(r#"EventMessageFile to String("C:\\Acme\\acme.exe")"#));
+}
+
+#[test]
+fn test_deregister_dry_run() {
+    let output = run_to_string(&["deregister", "Acme", "--dry-run"]).unwrap();
+
+    assert_eq!(
+        format!("delete key {APPLICATION_KEY}\\Acme with all subkeys\n"),
+        output
+    );
+}
+
+#[test]
+fn test_export_reg() {
+    let output =
+        run_to_string(&["export-reg", "Acme", "--message-file", r"C:\Acme\acme.exe"]).unwrap();
+
+    assert!(output.starts_with("Windows Registry Editor Version 5.00\r\n"));
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_registry_commands_need_windows() {
+    for args in [
+        &["register", "Acme", "--message-file", r"C:\Acme\acme.exe"][..],
+        &["deregister", "Acme"],
+        &["status", "Acme"],
+        &["list"],
+        &["test-event", "Acme"],
+    ] {
+        let err = run_to_string(args).unwrap_err();
+        assert!(err.to_string().contains("--dry-run"), "{args:?}: {err}");
+    }
+}
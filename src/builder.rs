@@ -0,0 +1,400 @@
+#[cfg(feature = "log")]
+use crate::EventLogLogger;
+#[cfg(windows)]
+use crate::{EventLog, WindowsRegistry, ensure_registered, reconnect::ReconnectingSink};
+use crate::{
+    EventLogLayer, MemWriter, MessageFiles, current_exe_path,
+    error::{EventLogError, RegistryError, Result},
+    fallback::{Fallback, FallbackSink},
+    fields::{EventLogFieldFormat, EventLogFields, FieldFilter},
+    lifecycle::LifecycleGuard,
+    mapping::EventMapping,
+    reconnect::RetryPolicy,
+    redact::{RedactionRule, Redactions},
+    reporter::Reporter,
+    sink::{EventSink, OpenSink},
+    stats::EventLogStats,
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{Level, Subscriber, level_filters::LevelFilter};
+use tracing_subscriber::fmt::format::{Compact, DefaultFields, Format, Pretty};
+use tracing_subscriber::fmt::{FormatEvent, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Configures an [`EventLogLayer`] before the event source is opened.
+pub struct EventLogLayerBuilder {
+    source: String,
+    server: Option<String>,
+    retry: RetryPolicy,
+    auto_register: bool,
+    message_files: Option<MessageFiles>,
+    fallbacks: Vec<Fallback>,
+    fallback_after: u32,
+    fallback_retry_interval: Duration,
+    stats: EventLogStats,
+    stats_report_interval: Option<Duration>,
+    lifecycle_events: bool,
+    app_version: Option<String>,
+    field_filter: FieldFilter,
+    redactions: Vec<RedactionRule>,
+    raw_data: bool,
+    max_level: LevelFilter,
+    levels: Vec<(Level, Level)>,
+    categories: Vec<(String, String)>,
+}
+
+impl EventLogLayerBuilder {
+    pub fn new<T: Into<String>>(source: T) -> Self {
+        Self {
+            source: source.into(),
+            server: None,
+            retry: RetryPolicy::default(),
+            auto_register: false,
+            message_files: None,
+            fallbacks: Vec::new(),
+            fallback_after: 3,
+            fallback_retry_interval: Duration::from_secs(30),
+            stats: EventLogStats::new(),
+            stats_report_interval: None,
+            lifecycle_events: false,
+            app_version: None,
+            field_filter: FieldFilter::default(),
+            redactions: Vec::new(),
+            raw_data: false,
+            max_level: LevelFilter::TRACE,
+            levels: Vec::new(),
+            categories: Vec::new(),
+        }
+    }
+
+    /// Writes to the event log of a remote computer, given as a computer name or `\\name`.
+    ///
+    /// With [`auto_register`](Self::auto_register), the source is registered in the remote
+    /// computer's registry for the given [`message_files`](Self::message_files).
+    pub fn server<T: Into<String>>(mut self, server: T) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// How long to wait before opening the event source again after re-registering it failed.
+    /// The delay doubles after every further failure, up to 5 minutes. Defaults to 5 seconds.
+    pub fn reconnect_interval(mut self, interval: Duration) -> Self {
+        self.retry.initial_backoff = interval;
+        self.retry.max_backoff = self.retry.max_backoff.max(interval);
+        self
+    }
+
+    /// Number of events kept while the event source can't be opened, to be written once it is
+    /// available again. Defaults to 16.
+    pub fn retry_buffer_size(mut self, size: usize) -> Self {
+        self.retry.buffer_size = size;
+        self
+    }
+
+    /// Checks that the source is registered with an existing message file when the layer is
+    /// built, and registers it for the current executable, or the
+    /// [`message_files`](Self::message_files), if it is not.
+    ///
+    /// Registering requires administrator rights. If it fails, building the layer returns an
+    /// error describing the problem with the current registration.
+    pub fn auto_register(mut self, auto_register: bool) -> Self {
+        self.auto_register = auto_register;
+        self
+    }
+
+    /// Message files to register with [`auto_register`](Self::auto_register) instead of the
+    /// current executable.
+    ///
+    /// Required to auto-register on a remote [`server`](Self::server), where they are paths on
+    /// that computer; building the layer fails with
+    /// [`RegistryError::RemoteMessageFilesRequired`] otherwise.
+    pub fn message_files(mut self, message_files: MessageFiles) -> Self {
+        self.message_files = Some(message_files);
+        self
+    }
+
+    /// Adds a fallback to write to when the event log can't be opened or keeps failing.
+    ///
+    /// Fallbacks are tried in the order they were added until one of them succeeds.
+    pub fn fallback(mut self, fallback: Fallback) -> Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
+    /// Number of consecutive failed reports before switching to the fallbacks. Defaults to 3.
+    pub fn fallback_after(mut self, failures: u32) -> Self {
+        self.fallback_after = failures;
+        self
+    }
+
+    /// How often to retry the event log while the fallbacks are in use. Defaults to 30 seconds.
+    pub fn fallback_retry_interval(mut self, interval: Duration) -> Self {
+        self.fallback_retry_interval = interval;
+        self
+    }
+
+    /// Records statistics into an existing handle instead of a new one.
+    pub fn stats(mut self, stats: EventLogStats) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Periodically writes the current statistics to the event log as an informational event.
+    ///
+    /// The check happens when an event is written, so nothing is reported while the
+    /// application is idle.
+    pub fn stats_report_interval(mut self, interval: Duration) -> Self {
+        self.stats_report_interval = Some(interval);
+        self
+    }
+
+    /// Writes a startup event when the layer is built and a shutdown event when its
+    /// [`LifecycleGuard`] is dropped.
+    pub fn lifecycle_events(mut self, lifecycle_events: bool) -> Self {
+        self.lifecycle_events = lifecycle_events;
+        self
+    }
+
+    /// Application version included in the startup event.
+    pub fn app_version<T: Into<String>>(mut self, version: T) -> Self {
+        self.app_version = Some(version.into());
+        self
+    }
+
+    /// Limits which fields are written to the event log. Defaults to [`FieldFilter::default`],
+    /// which only writes `message`, `error` and `category`; use [`FieldFilter::all`] to write
+    /// every field.
+    pub fn field_filter(mut self, filter: FieldFilter) -> Self {
+        self.field_filter = filter;
+        self
+    }
+
+    /// Adds a rule for removing sensitive values from the event log.
+    pub fn redact(mut self, rule: RedactionRule) -> Self {
+        self.redactions.push(rule);
+        self
+    }
+
+    /// Attaches the event's fields as `name=value` lines of UTF-8 raw data.
+    pub fn raw_data(mut self, raw_data: bool) -> Self {
+        self.raw_data = raw_data;
+        self
+    }
+
+    /// The most verbose level written to the event log. Other events are dropped and counted as
+    /// [`DropReason::Filtered`](crate::DropReason::Filtered), unlike with a filter around the
+    /// layer, which it never sees. Defaults to [`LevelFilter::TRACE`].
+    pub fn max_level(mut self, max_level: LevelFilter) -> Self {
+        self.max_level = max_level;
+        self
+    }
+
+    /// Writes events of level `from` as if they were of level `to`, e.g. to show warnings as
+    /// errors. This changes both the type and the message of the event.
+    pub fn map_level(mut self, from: Level, to: Level) -> Self {
+        self.levels.retain(|&(level, _)| level != from);
+        self.levels.push((from, to));
+        self
+    }
+
+    /// Writes events whose `category` field is `value` in `category`, ignoring case.
+    ///
+    /// `category` must be one of the categories compiled into the message table, see
+    /// `TRACING_EVENTLOG_CATEGORIES` in the build script; their names are part of the registered
+    /// message file, so new ones can't be added at runtime. Building the layer fails otherwise.
+    pub fn map_category<V: Into<String>, C: Into<String>>(mut self, value: V, category: C) -> Self {
+        self.categories.push((value.into(), category.into()));
+        self
+    }
+
+    pub fn build<S, N, F>(mut self, inner: Layer<S, N, F>) -> Result<EventLogLayer<S, N, F>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+        N: EventLogFieldFormat,
+        F: FormatEvent<S, EventLogFields<N>>,
+    {
+        let mapping = self.mapping()?;
+        let sink = self.open_sink()?;
+        Ok(self.build_with_sink(sink, mapping, inner))
+    }
+
+    /// Builds a `log` logger instead of a layer, e.g. for applications that only use `log`.
+    ///
+    /// Everything but the settings for fields and lifecycle events applies to the logger. Use
+    /// [`EventLogLayer::logger`] to write both through the same sink.
+    #[cfg(feature = "log")]
+    pub fn build_logger(mut self) -> Result<EventLogLogger> {
+        let mapping = self.mapping()?;
+        let sink = self.open_sink()?;
+        let reporter =
+            sink.map(|sink| Reporter::new(sink, self.stats.clone(), self.stats_report_interval));
+        Ok(EventLogLogger::from_parts(reporter, self.stats, mapping))
+    }
+
+    fn mapping(&self) -> Result<EventMapping> {
+        EventMapping::new(&self.levels, &self.categories)
+    }
+
+    /// The message files to auto-register, if enabled.
+    fn auto_register_files(&self) -> Result<Option<MessageFiles>> {
+        if !self.auto_register {
+            return Ok(None);
+        }
+        match (&self.message_files, &self.server) {
+            (Some(message_files), _) => Ok(Some(message_files.clone())),
+            (None, None) => Ok(Some(
+                MessageFiles::new().event_message_file(current_exe_path()?),
+            )),
+            (None, Some(server)) => {
+                Err(RegistryError::RemoteMessageFilesRequired(server.clone()).into())
+            }
+        }
+    }
+
+    fn open_sink(&mut self) -> Result<Option<Arc<dyn EventSink>>> {
+        let auto_register_files = self.auto_register_files()?;
+        #[cfg(windows)]
+        if let Some(message_files) = auto_register_files {
+            let registry = match &self.server {
+                Some(server) => WindowsRegistry::remote(server),
+                None => WindowsRegistry::new(),
+            };
+            // Message files of a remote registry are paths on that computer
+            ensure_registered(
+                &registry,
+                &self.source,
+                &message_files,
+                self.server.is_none(),
+            )?;
+        }
+        #[cfg(not(windows))]
+        let _ = auto_register_files;
+
+        if self.fallbacks.is_empty() {
+            return Ok(
+                open_event_log(self.server.as_deref(), &self.source, self.retry)?.map(Arc::from),
+            );
+        }
+        let server = self.server.clone();
+        let source = self.source.clone();
+        let retry = self.retry;
+        let open: OpenSink = Box::new(move || {
+            open_event_log(server.as_deref(), &source, retry)?.ok_or(EventLogError::Unavailable)
+        });
+        Ok(Some(Arc::new(FallbackSink::new(
+            self.source.clone(),
+            open,
+            std::mem::take(&mut self.fallbacks),
+            self.fallback_after,
+            self.fallback_retry_interval,
+        ))))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn build_with_test_sink<S, N, F>(
+        self,
+        sink: impl EventSink + 'static,
+        inner: Layer<S, N, F>,
+    ) -> EventLogLayer<S, N, F>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+        N: EventLogFieldFormat,
+        F: FormatEvent<S, EventLogFields<N>>,
+    {
+        let mapping = self.mapping().unwrap();
+        self.build_with_sink(Some(Arc::new(sink)), mapping, inner)
+    }
+
+    fn build_with_sink<S, N, F>(
+        self,
+        sink: Option<Arc<dyn EventSink>>,
+        mapping: EventMapping,
+        inner: Layer<S, N, F>,
+    ) -> EventLogLayer<S, N, F>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+        N: EventLogFieldFormat,
+        F: FormatEvent<S, EventLogFields<N>>,
+    {
+        let reporter =
+            sink.map(|sink| Reporter::new(sink, self.stats.clone(), self.stats_report_interval));
+        let lifecycle = reporter
+            .clone()
+            .filter(|_| self.lifecycle_events)
+            .map(|reporter| {
+                LifecycleGuard::start(
+                    reporter,
+                    self.stats.clone(),
+                    &self.source,
+                    self.app_version.as_deref(),
+                )
+            });
+
+        let field_filter = Arc::new(self.field_filter);
+        let redactions = Redactions::new(self.redactions);
+        let inner = inner
+            .map_fmt_fields(|fields| {
+                EventLogFields::new(fields, field_filter.clone(), redactions.clone())
+            })
+            .with_writer(MemWriter {});
+        EventLogLayer {
+            inner,
+            reporter,
+            stats: self.stats,
+            lifecycle,
+            field_filter,
+            redactions,
+            raw_data: self.raw_data,
+            max_level: self.max_level,
+            mapping,
+        }
+    }
+
+    pub fn pretty<S>(self) -> Result<EventLogLayer<S, Pretty, Format<Pretty, ()>>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        self.build(
+            tracing_subscriber::fmt::layer()
+                .pretty()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false),
+        )
+    }
+
+    pub fn compact<S>(self) -> Result<EventLogLayer<S, DefaultFields, Format<Compact, ()>>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        self.build(
+            tracing_subscriber::fmt::layer()
+                .compact()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false),
+        )
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn open_event_log(
+    server: Option<&str>,
+    source: &str,
+    retry: RetryPolicy,
+) -> Result<Option<Box<dyn EventSink>>> {
+    let server = server.map(str::to_owned);
+    let source = source.to_owned();
+    let open: OpenSink =
+        Box::new(move || Ok(Box::new(EventLog::new(server.as_deref(), source.clone())?)));
+    Ok(Some(Box::new(ReconnectingSink::new(open, retry)?)))
+}
+
+#[cfg(not(windows))]
+pub(crate) fn open_event_log(
+    _server: Option<&str>,
+    _source: &str,
+    _retry: RetryPolicy,
+) -> Result<Option<Box<dyn EventSink>>> {
+    Ok(None)
+}
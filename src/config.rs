@@ -0,0 +1,333 @@
+use crate::{
+    CustomLogOptions, EventLogLayerBuilder, EventLogRegistry, FieldFilter, LogSource, MessageFiles,
+    Retention, SecurityDescriptor,
+    error::{self, ConfigError, RegistryError},
+    eventmsgs,
+    registry::validate_new_name,
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::{Layer, filter::LevelFilter, registry::LookupSpan};
+
+/// Describes an event source and the layer writing to it, e.g. as a section of an
+/// application's TOML or YAML configuration.
+///
+/// ```toml
+/// source = "My App"
+/// level = "info"
+/// format = "pretty"
+///
+/// [log]
+/// type = "custom"
+/// name = "My Company"
+/// max_size = 1048576
+/// retention = "never"
+///
+/// [filter]
+/// base = "all"
+/// deny_fields = ["password"]
+///
+/// [levels]
+/// warn = "error"
+///
+/// [categories]
+/// db = "Database Events"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventLogConfig {
+    pub source: String,
+    #[serde(default)]
+    pub log: LogConfig,
+    /// Computer whose event log is written to, the local machine if not set.
+    pub server: Option<String>,
+    #[serde(default)]
+    pub message_files: MessageFilesConfig,
+    /// The most verbose level written to the event log, e.g. `"info"`. Everything is written
+    /// if not set.
+    pub level: Option<String>,
+    #[serde(default)]
+    pub format: FormatConfig,
+    #[serde(default)]
+    pub filter: FilterConfig,
+    /// Levels written as another level, see [`EventLogLayerBuilder::map_level`].
+    #[serde(default)]
+    pub levels: BTreeMap<String, String>,
+    /// Values of the `category` field and the categories they are written in, see
+    /// [`EventLogLayerBuilder::map_category`]. Only the categories compiled into the message
+    /// table can be used: their names are part of the registered message file.
+    #[serde(default)]
+    pub categories: BTreeMap<String, String>,
+    #[serde(default)]
+    pub raw_data: bool,
+    #[serde(default)]
+    pub lifecycle_events: bool,
+}
+
+/// The log the source belongs to.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LogConfig {
+    #[default]
+    Application,
+    Custom {
+        name: String,
+        /// Other sources registered in the log besides the configured one.
+        #[serde(default)]
+        sources: Vec<String>,
+        /// In bytes, a multiple of 64 KB.
+        max_size: Option<u32>,
+        #[serde(default)]
+        retention: RetentionConfig,
+        #[serde(default)]
+        auto_backup: bool,
+        file: Option<String>,
+        /// SDDL restricting access to the log, see [`SecurityDescriptor`].
+        security_descriptor: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionConfig {
+    #[default]
+    Overwrite,
+    OverwriteOlderThanSecs(u64),
+    Never,
+}
+
+/// Message files to register instead of the current executable.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageFilesConfig {
+    #[serde(default)]
+    pub event: Vec<String>,
+    #[serde(default)]
+    pub category: Vec<String>,
+    #[serde(default)]
+    pub parameter: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatConfig {
+    #[default]
+    Compact,
+    Pretty,
+}
+
+/// Which fields are written, see [`FieldFilter`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilterConfig {
+    /// The fields allowed before the rules below are applied.
+    #[serde(default)]
+    pub base: FilterBase,
+    #[serde(default)]
+    pub allow_fields: Vec<String>,
+    #[serde(default)]
+    pub deny_fields: Vec<String>,
+    #[serde(default)]
+    pub allow_targets: Vec<String>,
+    #[serde(default)]
+    pub deny_targets: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterBase {
+    /// `message`, `error` and `category`, like [`FieldFilter::default`].
+    #[default]
+    Default,
+    All,
+    None,
+}
+
+impl FilterConfig {
+    fn field_filter(&self) -> FieldFilter {
+        let filter = match self.base {
+            FilterBase::Default => FieldFilter::default(),
+            FilterBase::All => FieldFilter::all(),
+            FilterBase::None => FieldFilter::none(),
+        };
+        let filter = self
+            .allow_fields
+            .iter()
+            .fold(filter, |f, p| f.allow_field(p));
+        let filter = self.deny_fields.iter().fold(filter, |f, p| f.deny_field(p));
+        let filter = self
+            .allow_targets
+            .iter()
+            .fold(filter, |f, p| f.allow_target(p));
+        self.deny_targets
+            .iter()
+            .fold(filter, |f, p| f.deny_target(p))
+    }
+}
+
+impl RetentionConfig {
+    fn retention(self) -> Retention {
+        match self {
+            Self::Overwrite => Retention::Overwrite,
+            Self::OverwriteOlderThanSecs(secs) => {
+                Retention::OverwriteOlderThan(Duration::from_secs(secs))
+            }
+            Self::Never => Retention::Never,
+        }
+    }
+}
+
+fn check(key: &str, result: Result<(), RegistryError>) -> Result<(), ConfigError> {
+    result.map_err(|e| ConfigError::new(key, e.to_string()))
+}
+
+fn parse_level(key: &str, level: &str) -> Result<Level, ConfigError> {
+    level.parse().map_err(|_| {
+        ConfigError::new(
+            key,
+            format!("expected one of error, warn, info, debug or trace, got '{level}'"),
+        )
+    })
+}
+
+impl EventLogConfig {
+    /// The source to register, checked without accessing the registry.
+    pub fn log_source(&self) -> Result<LogSource, ConfigError> {
+        check("source", validate_new_name(&self.source))?;
+        let source = match &self.log {
+            LogConfig::Application => LogSource::application(&self.source),
+            LogConfig::Custom {
+                name,
+                sources,
+                max_size,
+                retention,
+                auto_backup,
+                file,
+                security_descriptor,
+            } => {
+                check("log.name", validate_new_name(name))?;
+                for (i, source) in sources.iter().enumerate() {
+                    check(&format!("log.sources[{i}]"), validate_new_name(source))?;
+                }
+                let mut options = CustomLogOptions::new();
+                if let Some(max_size) = *max_size {
+                    options = options.max_size(max_size);
+                    check("log.max_size", options.validate())?;
+                }
+                options = options.retention(retention.retention());
+                check("log.retention", options.validate())?;
+                options = options.auto_backup(*auto_backup);
+                check("log.auto_backup", options.validate())?;
+                if let Some(file) = file {
+                    options = options.file(file);
+                    check("log.file", options.validate())?;
+                }
+                if let Some(sddl) = security_descriptor {
+                    let descriptor = SecurityDescriptor::parse(sddl.as_str())
+                        .map_err(|e| ConfigError::new("log.security_descriptor", e.to_string()))?;
+                    options = options.security_descriptor(descriptor);
+                }
+                let sources = std::iter::once(self.source.as_str())
+                    .chain(sources.iter().map(String::as_str))
+                    .collect::<Vec<_>>();
+                LogSource::custom_with_options(name, sources, options)
+            }
+        };
+
+        let files = &self.message_files;
+        for (key, paths) in [
+            ("message_files.event", &files.event),
+            ("message_files.category", &files.category),
+            ("message_files.parameter", &files.parameter),
+        ] {
+            for (i, path) in paths.iter().enumerate() {
+                let file = MessageFiles::new().event_message_file(path);
+                check(&format!("{key}[{i}]"), file.validate(false))?;
+            }
+        }
+        let message_files = files
+            .event
+            .iter()
+            .fold(MessageFiles::new(), |m, f| m.event_message_file(f));
+        let message_files = files
+            .category
+            .iter()
+            .fold(message_files, |m, f| m.category_message_file(f));
+        let message_files = files
+            .parameter
+            .iter()
+            .fold(message_files, |m, f| m.parameter_message_file(f));
+        // Only an event message file can be missing at this point
+        if !message_files.is_empty() {
+            check("message_files.event", message_files.validate(false))?;
+        }
+
+        let mut source = source.with_message_files(message_files);
+        if let Some(server) = &self.server {
+            source = source.with_server(server);
+        }
+        Ok(source)
+    }
+
+    /// A builder for the layer, with everything but the format configured.
+    pub fn layer_builder(&self) -> Result<EventLogLayerBuilder, ConfigError> {
+        check("source", validate_new_name(&self.source))?;
+        let mut builder = EventLogLayerBuilder::new(&self.source)
+            .field_filter(self.filter.field_filter())
+            .raw_data(self.raw_data)
+            .lifecycle_events(self.lifecycle_events);
+        for (from, to) in &self.levels {
+            let key = format!("levels.{from}");
+            builder = builder.map_level(parse_level(&key, from)?, parse_level(&key, to)?);
+        }
+        for (value, category) in &self.categories {
+            if eventmsgs::find_category(category).is_none() {
+                return Err(ConfigError::new(
+                    &format!("categories.{value}"),
+                    format!("'{category}' is not one of the categories compiled into this crate"),
+                ));
+            }
+            builder = builder.map_category(value, category);
+        }
+        if let Some(level) = self.level_filter()? {
+            builder = builder.max_level(level);
+        }
+        if let Some(server) = &self.server {
+            builder = builder.server(server);
+        }
+        Ok(builder)
+    }
+
+    fn level_filter(&self) -> Result<Option<LevelFilter>, ConfigError> {
+        self.level
+            .as_deref()
+            .map(|level| {
+                level.parse().map_err(|_| {
+                    ConfigError::new(
+                        "level",
+                        format!(
+                            "expected one of off, error, warn, info, debug or trace, got '{level}'"
+                        ),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Builds the layer with the configured level and format.
+    pub fn build_layer<S>(&self) -> error::Result<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        let builder = self.layer_builder()?;
+        Ok(match self.format {
+            FormatConfig::Compact => builder.compact()?.boxed(),
+            FormatConfig::Pretty => builder.pretty()?.boxed(),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "./config_test.rs"]
+mod config_test;
@@ -0,0 +1,172 @@
+use super::*;
+use crate::{InMemoryRegistry, RegistryBackend, RegistryValue};
+use tracing_subscriber::Registry;
+
+const LOG_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\My Company";
+
+fn parse(json: &str) -> EventLogConfig {
+    serde_json::from_str(json).unwrap()
+}
+
+fn key_of(config: &EventLogConfig) -> String {
+    config.log_source().err().unwrap().key
+}
+
+fn custom_named(name: &str) -> EventLogConfig {
+    parse(&format!(
+        r#"{{"source": "My App", "log": {{"type": "custom", "name": "{name}"}}}}"#
+    ))
+}
+
+#[test]
+fn test_minimal_config() {
+    let config = parse(r#"{"source": "My App"}"#);
+
+    assert!(matches!(config.log, LogConfig::Application));
+    assert_eq!(FormatConfig::Compact, config.format);
+    assert!(config.log_source().is_ok());
+}
+
+#[test]
+fn test_custom_log_source() {
+    let config = parse(
+        r#"{
+            "source": "My App",
+            "message_files": {"event": ["%CARGO_MANIFEST_DIR%/Cargo.toml"]},
+            "log": {
+                "type": "custom",
+                "name": "My Company",
+                "sources": ["My Service"],
+                "max_size": 1048576,
+                "retention": "never",
+                "auto_backup": true,
+                "security_descriptor": "D:(A;;0x3;;;BA)"
+            }
+        }"#,
+    );
+    let registry = InMemoryRegistry::new();
+
+    config
+        .log_source()
+        .unwrap()
+        .register_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        vec!["My App", "My Company", "My Service"],
+        registry.subkeys(LOG_KEY).unwrap()
+    );
+    assert_eq!(
+        Some(RegistryValue::U32(1048576)),
+        registry.get_value(LOG_KEY, "MaxSize").unwrap()
+    );
+    assert_eq!(
+        Some(RegistryValue::String("D:(A;;0x3;;;BA)".to_owned())),
+        registry.get_value(LOG_KEY, "CustomSD").unwrap()
+    );
+}
+
+#[test]
+fn test_errors_point_at_key() {
+    let custom = |option: &str| {
+        parse(&format!(
+            r#"{{"source": "My App", "log": {{"type": "custom", "name": "Log", {option}}}}}"#
+        ))
+    };
+
+    assert_eq!("log.max_size", key_of(&custom(r#""max_size": 1000"#)));
+    assert_eq!("log.auto_backup", key_of(&custom(r#""auto_backup": true"#)));
+    assert_eq!("log.file", key_of(&custom(r#""file": "log.txt""#)));
+    assert_eq!(
+        "log.retention",
+        key_of(&custom(r#""retention": {"overwrite_older_than_secs": 0}"#))
+    );
+    assert_eq!(
+        "log.security_descriptor",
+        key_of(&custom(r#""security_descriptor": "D:(A;;0x3;;;Admins)""#))
+    );
+    assert_eq!(
+        "log.sources[1]",
+        key_of(&custom(r#""sources": ["a", "b\\c"]"#))
+    );
+    assert_eq!("source", key_of(&parse(r#"{"source": ""}"#)));
+    assert_eq!("log.name", key_of(&custom_named("System")));
+    assert_eq!(
+        "message_files.category[1]",
+        key_of(&parse(
+            r#"{"source": "My App", "message_files": {"event": ["app.dll"], "category": ["a.dll", "b.dll;c.dll"]}}"#
+        ))
+    );
+    assert_eq!(
+        "message_files.event",
+        key_of(&parse(
+            r#"{"source": "My App", "message_files": {"category": ["app.dll"]}}"#
+        ))
+    );
+
+    let layer_key = |json: &str| parse(json).layer_builder().err().unwrap().key;
+    assert_eq!(
+        "levels.warn",
+        layer_key(r#"{"source": "My App", "levels": {"warn": "fatal"}}"#)
+    );
+    assert_eq!(
+        "categories.db",
+        layer_key(r#"{"source": "My App", "categories": {"db": "Storage"}}"#)
+    );
+
+    let err = parse(r#"{"source": "My App", "level": "loud"}"#)
+        .build_layer::<Registry>()
+        .err()
+        .unwrap();
+    assert_eq!(
+        "Invalid value for `level`: expected one of off, error, warn, info, debug or trace, \
+        got 'loud'",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_unknown_keys_are_rejected() {
+    for json in [
+        r#"{"source": "My App", "colour": true}"#,
+        r#"{"source": "My App", "log": {"type": "custom", "name": "Log", "size": 1}}"#,
+        r#"{"source": "My App", "filter": {"deny": ["password"]}}"#,
+    ] {
+        let err = serde_json::from_str::<EventLogConfig>(json).unwrap_err();
+        assert!(err.to_string().contains("unknown field"), "{err}");
+    }
+}
+
+#[test]
+fn test_field_filter() {
+    let config = parse(
+        r#"{"source": "My App", "filter": {"base": "all", "deny_fields": ["password"], "deny_targets": ["db*"]}}"#,
+    );
+
+    let filter = config.filter.field_filter();
+
+    assert!(filter.allows("app", "user"));
+    assert!(!filter.allows("app", "password"));
+    assert!(!filter.allows("db::pool", "user"));
+
+    let filter = parse(r#"{"source": "My App"}"#).filter.field_filter();
+
+    assert!(filter.allows("app", "message"));
+    assert!(!filter.allows("app", "user"));
+}
+
+#[test]
+fn test_build_layer() {
+    let config = parse(r#"{"source": "My App", "level": "warn", "format": "pretty"}"#);
+
+    assert!(config.build_layer::<Registry>().is_ok());
+}
+
+#[test]
+fn test_level_and_category_mapping() {
+    let config = parse(
+        r#"{"source": "My App", "levels": {"warn": "error"}, "categories": {"db": "Database Events"}}"#,
+    );
+
+    assert!(config.build_layer::<Registry>().is_ok());
+}
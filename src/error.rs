@@ -1,3 +1,4 @@
+use crate::RegistrationProblem;
 use thiserror::Error;
 use widestring::error::ContainsNul;
 
@@ -15,9 +16,80 @@ pub enum EventLogError {
     WindowsError(String),
     #[error("OS error occured during Windows API call: {0}")]
     SystemError(#[from] std::io::Error),
+    #[error("Error registering event source: {0}")]
+    RegistryError(#[from] RegistryError),
+    #[error("The event log is unavailable")]
+    Unavailable,
+    /// A category that isn't one of those compiled into the message table.
+    #[error("Unknown event category '{0}'")]
+    UnknownCategory(String),
+    /// The event log is unavailable, but the event was kept to be written once it is back.
+    #[error("The event log is unavailable, the event was buffered to be written later")]
+    Buffered,
+    /// A lock needed to write the event is held elsewhere, see [`EventSink::try_report`].
+    ///
+    /// [`EventSink::try_report`]: crate::EventSink::try_report
+    #[error("The event sink is in use")]
+    Busy,
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    ConfigError(#[from] ConfigError),
 }
 
+// ERROR_INVALID_HANDLE, RPC_S_SERVER_UNAVAILABLE, RPC_S_SERVER_TOO_BUSY, RPC_S_CALL_FAILED and
+// EPT_S_NOT_REGISTERED, returned while the EventLog service restarts or a server is unreachable
 #[cfg(windows)]
+const TRANSIENT_ERRORS: [u32; 5] = [6, 1722, 1723, 1726, 1753];
+
+impl EventLogError {
+    /// Whether the error may go away by opening the event source again, e.g. after the EventLog
+    /// service restarted. Other errors are permanent and retrying them won't help.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            #[cfg(windows)]
+            Self::WindowsError(e) => TRANSIENT_ERRORS.iter().any(|&code| {
+                e.code() == windows::Win32::Foundation::WIN32_ERROR(code).to_hresult()
+            }),
+            Self::SystemError(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::NotConnected
+            ),
+            Self::Unavailable => true,
+            _ => false,
+        }
+    }
+
+    /// A short, stable name for the kind of error, used to group failures in statistics.
+    ///
+    /// Windows API errors are identified by their `HRESULT`.
+    pub fn kind(&self) -> String {
+        match self {
+            Self::StrConvertError(_) => "invalid_string".to_owned(),
+            #[cfg(windows)]
+            Self::WindowsError(e) => format!("{:#010X}", e.code().0),
+            #[cfg(not(windows))]
+            Self::WindowsError(_) => "windows".to_owned(),
+            Self::SystemError(e) => format!("io_{:?}", e.kind()).to_lowercase(),
+            Self::RegistryError(_) => "registry".to_owned(),
+            Self::Unavailable => "unavailable".to_owned(),
+            Self::Buffered => "buffered".to_owned(),
+            Self::Busy => "busy".to_owned(),
+            Self::UnknownCategory(_) => "unknown_category".to_owned(),
+            #[cfg(feature = "serde")]
+            Self::ConfigError(_) => "config".to_owned(),
+        }
+    }
+}
+
+#[cfg(windows)]
+type OsError = ::windows::core::Error;
+#[cfg(not(windows))]
+type OsError = String;
+
 #[derive(Error, Debug)]
 pub enum RegistryError {
     #[error("OS error occured during Windows API call: {0}")]
@@ -25,26 +97,78 @@ pub enum RegistryError {
     #[error("Unable to locate current exe path")]
     InvalidExePath,
     #[error("Permission denied: {0}")]
-    PermissionDenied(::windows::core::Error),
+    PermissionDenied(OsError),
     #[error("Error settings registry key: {0}")]
-    KeyError(::windows::core::Error),
+    KeyError(OsError),
     #[error("Error setting registry value: {0}")]
-    ValueError(::windows::core::Error),
+    ValueError(OsError),
+    #[error("Registry key not found: {0}")]
+    KeyNotFound(String),
+    #[error("Event source and log names must not be empty")]
+    EmptyName,
+    #[error("Name '{name}' contains {character:?}, which is not allowed in source and log names")]
+    InvalidNameCharacter { name: String, character: char },
+    #[error("'{0}' is the name of an event log and can't be used for another source or log")]
+    ReservedName(String),
+    #[error(
+        "Custom log '{log}' starts with the same 8 characters as the existing log '{existing}'; \
+        the event log service can't tell them apart"
+    )]
+    LogPrefixCollision { log: String, existing: String },
+    #[error("Event source '{name}' is already registered in the '{log}' log")]
+    SourceInOtherLog { name: String, log: String },
+    #[error("Invalid custom log option: {0}")]
+    InvalidLogOption(String),
+    #[error("Invalid security descriptor '{sddl}' at offset {offset}: {reason}")]
+    InvalidSecurityDescriptor {
+        sddl: String,
+        offset: usize,
+        reason: String,
+    },
+    #[error("Invalid message file '{path}': {reason}")]
+    InvalidMessageFile { path: String, reason: String },
+    #[error("{cause}; rolling back the changes made so far also failed: {rollback}")]
+    RollbackFailed {
+        cause: Box<RegistryError>,
+        rollback: Box<RegistryError>,
+    },
+    #[error(
+        "Auto-registering the event source on '{0}' needs explicit message files; the path of \
+        the current executable doesn't exist on that computer"
+    )]
+    RemoteMessageFilesRequired(String),
+    #[error(
+        "Event source '{name}' is not usable ({problem}) and could not be registered: {cause}. \
+        Run the application as an administrator once or call LogSource::register() from an \
+        elevated installer"
+    )]
+    RegistrationFailed {
+        name: String,
+        problem: RegistrationProblem,
+        cause: Box<RegistryError>,
+    },
 }
 
-#[cfg(not(windows))]
+/// An invalid value in an [`EventLogConfig`](crate::EventLogConfig).
+#[cfg(feature = "serde")]
 #[derive(Error, Debug)]
-pub enum RegistryError {
-    #[error("")]
-    SystemError(String),
-    #[error("")]
-    InvalidExePath(String),
-    #[error("")]
-    PermissionDenied(String),
-    #[error("")]
-    KeyError(String),
-    #[error("")]
-    ValueError(String),
-    #[error("")]
-    StrConvertError(String),
+#[error("Invalid value for `{key}`: {message}")]
+pub struct ConfigError {
+    /// Path of the offending key, e.g. `log.max_size`.
+    pub key: String,
+    pub message: String,
+}
+
+#[cfg(feature = "serde")]
+impl ConfigError {
+    pub(crate) fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_owned(),
+            message: message.into(),
+        }
+    }
 }
+
+#[cfg(test)]
+#[path = "./error_test.rs"]
+mod error_test;
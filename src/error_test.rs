@@ -0,0 +1,41 @@
+use super::*;
+
+#[test]
+fn test_transient_errors() {
+    assert!(EventLogError::Unavailable.is_transient());
+    assert!(
+        EventLogError::SystemError(std::io::Error::from(std::io::ErrorKind::TimedOut))
+            .is_transient()
+    );
+    assert!(
+        !EventLogError::SystemError(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+            .is_transient()
+    );
+    assert!(!EventLogError::Buffered.is_transient());
+    assert!(!EventLogError::RegistryError(RegistryError::InvalidExePath).is_transient());
+}
+
+#[cfg(windows)]
+fn win32_error(code: u32) -> EventLogError {
+    EventLogError::WindowsError(windows::Win32::Foundation::WIN32_ERROR(code).into())
+}
+
+#[cfg(windows)]
+#[test]
+fn test_transient_win32_errors() {
+    // ERROR_INVALID_HANDLE, RPC_S_SERVER_UNAVAILABLE, RPC_S_SERVER_TOO_BUSY, RPC_S_CALL_FAILED
+    // and EPT_S_NOT_REGISTERED
+    assert_eq!(windows::Win32::Foundation::ERROR_INVALID_HANDLE.0, 6);
+    for code in [6, 1722, 1723, 1726, 1753] {
+        assert!(win32_error(code).is_transient(), "{code}");
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn test_permanent_win32_errors() {
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+
+    assert!(!win32_error(ERROR_ACCESS_DENIED.0).is_transient());
+    assert!(!win32_error(ERROR_INVALID_PARAMETER.0).is_transient());
+}
@@ -1,5 +1,3 @@
-#[cfg(test)]
-use mockall::automock;
 use tracing::Level;
 use widestring::WideCString;
 use windows::{
@@ -15,7 +13,7 @@ use windows::{
 
 use crate::{
     error::{EventLogError, Result},
-    eventmsgs,
+    sink::{EventRecord, EventSink},
 };
 
 pub(crate) struct EventLog {
@@ -27,60 +25,54 @@ unsafe impl Send for EventLog {}
 
 unsafe impl Sync for EventLog {}
 
-pub(crate) struct MessageType {
-    message_type: REPORT_EVENT_TYPE,
-    level: u32,
-}
-
-impl From<Level> for MessageType {
-    fn from(level: Level) -> Self {
-        let (message_type, level) = match level {
-            Level::ERROR => (EVENTLOG_ERROR_TYPE, eventmsgs::MSG_ERROR),
-            Level::WARN => (EVENTLOG_WARNING_TYPE, eventmsgs::MSG_WARNING),
-            Level::INFO => (EVENTLOG_INFORMATION_TYPE, eventmsgs::MSG_INFO),
-            Level::DEBUG => (EVENTLOG_INFORMATION_TYPE, eventmsgs::MSG_DEBUG),
-            Level::TRACE => (EVENTLOG_INFORMATION_TYPE, eventmsgs::MSG_TRACE),
-        };
-        Self {
-            message_type,
-            level,
-        }
+fn event_type(level: Level) -> REPORT_EVENT_TYPE {
+    match level {
+        Level::ERROR => EVENTLOG_ERROR_TYPE,
+        Level::WARN => EVENTLOG_WARNING_TYPE,
+        Level::INFO | Level::DEBUG | Level::TRACE => EVENTLOG_INFORMATION_TYPE,
     }
 }
 
-#[cfg_attr(test, automock)]
 impl EventLog {
-    pub(crate) fn new<T: Into<String> + 'static>(source: T) -> Result<Self> {
+    /// Opens `source` on `server`, or on the local machine if it is `None`.
+    pub(crate) fn new<T: Into<String> + 'static>(server: Option<&str>, source: T) -> Result<Self> {
+        let server = server
+            .map(WideCString::from_str)
+            .transpose()
+            .map_err(EventLogError::StrConvertError)?;
         let source =
             WideCString::from_os_str(source.into()).map_err(EventLogError::StrConvertError)?;
+        let win_server = server
+            .as_ref()
+            .map_or(PCWSTR::null(), |server| PCWSTR::from_raw(server.as_ptr()));
         let win_source = PCWSTR::from_raw(source.as_ptr());
         let handle = unsafe {
-            WinEventLog::RegisterEventSourceW(PCWSTR::null(), win_source)
+            WinEventLog::RegisterEventSourceW(win_server, win_source)
                 .map_err(EventLogError::WindowsError)?
         };
         Ok(Self { handle })
     }
 
-    pub(crate) fn report_event<T: Into<MessageType> + 'static>(
+    pub(crate) fn report_event(
         &self,
-        message_type: T,
+        event_type: REPORT_EVENT_TYPE,
         category: u16,
+        event_id: u32,
         mut message: WideCString,
+        raw_data: &[u8],
     ) -> Result<()> {
-        let message_type: MessageType = message_type.into();
-
         let pwstrs = vec![windows::core::PCWSTR::from_raw(message.as_mut_ptr())];
 
         unsafe {
             WinEventLog::ReportEventW(
                 self.handle,
-                message_type.message_type,
+                event_type,
                 category,
-                message_type.level,
+                event_id,
                 None,
-                0,
+                raw_data.len() as u32,
                 Some(pwstrs.as_slice()),
-                None,
+                (!raw_data.is_empty()).then_some(raw_data.as_ptr().cast()),
             )
         }?;
 
@@ -88,6 +80,18 @@ impl EventLog {
     }
 }
 
+impl EventSink for EventLog {
+    fn report(&self, record: &EventRecord) -> Result<()> {
+        self.report_event(
+            event_type(record.level),
+            record.category,
+            record.event_id,
+            WideCString::from_str_truncate(&record.message),
+            &record.raw_data,
+        )
+    }
+}
+
 impl Drop for EventLog {
     fn drop(&mut self) {
         let result = unsafe { WinEventLog::DeregisterEventSource(self.handle) };
@@ -1,2 +1,15 @@
-// build.rs generates a rust snippet with constants from res/eventmsgs.h into res/eventmsgs.rs.
-include!("../res/eventmsgs.rs");
+// build.rs compiles res/eventmsgs.mc.tmpl into constants for the message and category IDs.
+include!(concat!(env!("OUT_DIR"), "/eventmsgs.rs"));
+
+/// The ID of the category named `name`, ignoring case.
+pub(crate) fn find_category(name: &str) -> Option<u16> {
+    Some(get_category(format!("{name:?}"))).filter(|&id| id != 0)
+}
+
+#[cfg(test)]
+#[path = "../build/message_table.rs"]
+mod message_table;
+
+#[cfg(test)]
+#[path = "./eventmsgs_test.rs"]
+mod eventmsgs_test;
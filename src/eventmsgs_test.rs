@@ -0,0 +1,178 @@
+use super::message_table::*;
+
+const TEMPLATE: &str = include_str!("../res/eventmsgs.mc.tmpl");
+
+fn language(name: &str, id: u16, categories: &[&str], messages: &[(&str, &str)]) -> Language {
+    Language {
+        name: name.to_owned(),
+        id,
+        categories: categories.iter().map(|&c| c.to_owned()).collect(),
+        messages: messages
+            .iter()
+            .map(|&(symbol, text)| (symbol.to_owned(), text.to_owned()))
+            .collect(),
+    }
+}
+
+fn languages() -> Vec<Language> {
+    vec![
+        language(
+            DEFAULT_LANGUAGE,
+            DEFAULT_LANGUAGE_ID,
+            &["Database Events"],
+            &[("MSG_PANIC", "Panic: %1")],
+        ),
+        language(
+            "German",
+            0x407,
+            &["Datenbankereignisse", "Absturz", "Start", "Beenden"],
+            &[],
+        ),
+    ]
+}
+
+fn categories() -> Vec<&'static str> {
+    let mut categories = vec!["Database Events"];
+    categories.extend(BUILTIN_CATEGORIES);
+    categories
+}
+
+fn messages() -> Vec<Message> {
+    parse(&message_source(TEMPLATE, &categories(), &languages()))
+}
+
+fn message<'a>(messages: &'a [Message], symbol: &str) -> &'a Message {
+    messages.iter().find(|m| m.symbol == symbol).unwrap()
+}
+
+fn text<'a>(message: &'a Message, language: &str) -> &'a str {
+    let (_, text) = message.texts.iter().find(|(l, _)| l == language).unwrap();
+    text
+}
+
+#[test]
+fn test_declares_languages() {
+    let source = message_source(TEMPLATE, &categories(), &languages());
+
+    assert!(source.contains("LanguageNames=(English=0x409:MSG00409)\n"));
+    assert!(source.contains("LanguageNames=(German=0x407:MSG00407)\n"));
+    assert!(!source.contains('{'), "unfilled placeholder");
+}
+
+#[test]
+fn test_every_message_has_text_in_every_language() {
+    let messages = messages();
+
+    assert_eq!(12, messages.len());
+    for message in messages {
+        let languages = message
+            .texts
+            .iter()
+            .map(|(l, _)| l.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["English", "German"], languages, "{}", message.symbol);
+    }
+}
+
+#[test]
+fn test_categories_are_localized() {
+    let messages = messages();
+
+    let categories = messages.iter().filter(|m| m.word).collect::<Vec<_>>();
+    assert_eq!(4, categories.len());
+    assert_eq!(
+        "Absturz",
+        text(message(&messages, "PANIC_CATEGORY"), "German")
+    );
+    assert_eq!(
+        "Database Events",
+        text(message(&messages, "DATABASE_EVENTS_CATEGORY"), "English")
+    );
+}
+
+#[test]
+fn test_messages_fall_back_to_default_language() {
+    let messages = messages();
+
+    let panic = message(&messages, "MSG_PANIC");
+    assert_eq!("Panic: %1", text(panic, "English"));
+    assert_eq!("Panic: %1", text(panic, "German"));
+    assert_eq!("%1", text(message(&messages, "MSG_INFO"), "German"));
+}
+
+#[test]
+fn test_message_ids() {
+    let messages = messages();
+
+    assert_eq!(1, message(&messages, "DATABASE_EVENTS_CATEGORY").id);
+    assert_eq!(4, message(&messages, "SHUTDOWN_CATEGORY").id);
+    assert_eq!(0xC0000100, message(&messages, "MSG_ERROR").id);
+    assert_eq!(0x80000101, message(&messages, "MSG_WARNING").id);
+    assert_eq!(0x40000102, message(&messages, "MSG_INFO").id);
+    assert_eq!(0xC0000105, message(&messages, "MSG_PANIC").id);
+}
+
+#[test]
+fn test_message_table_layout() {
+    let messages = vec![
+        Message {
+            symbol: "MSG_INFO".to_owned(),
+            id: 0x40000102,
+            word: false,
+            texts: vec![("English".to_owned(), "%1".to_owned())],
+        },
+        Message {
+            symbol: "A_CATEGORY".to_owned(),
+            id: 1,
+            word: true,
+            texts: vec![("English".to_owned(), "A".to_owned())],
+        },
+    ];
+
+    #[rustfmt::skip]
+    let expected: Vec<u8> = vec![
+        // Two blocks sorted by ID, with the offsets of their entries
+        2, 0, 0, 0,
+        1, 0, 0, 0, 1, 0, 0, 0, 28, 0, 0, 0,
+        0x02, 0x01, 0, 0x40, 0x02, 0x01, 0, 0x40, 40, 0, 0, 0,
+        // Length and Unicode flag, then "A\r\n" terminated with NUL
+        12, 0, 1, 0, b'A', 0, b'\r', 0, b'\n', 0, 0, 0,
+        // "%1\r\n" with NUL and padding
+        16, 0, 1, 0, b'%', 0, b'1', 0, b'\r', 0, b'\n', 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(expected, message_table(&messages, "English"));
+    assert_eq!(vec![0, 0, 0, 0], message_table(&messages, "German"));
+}
+
+#[test]
+fn test_resource_file_has_table_per_language() {
+    let messages = messages();
+    let languages = languages();
+
+    let resource = resource_file(&messages, &languages);
+
+    // An empty resource, as rc.exe writes first
+    assert_eq!(resource[..8], [0, 0, 0, 0, 0x20, 0, 0, 0]);
+    let mut offset = 32;
+    for language in &languages {
+        let header = &resource[offset..offset + 32];
+        let data_size = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        // RT_MESSAGETABLE with ID 1
+        assert_eq!(header[8..16], [0xFF, 0xFF, 11, 0, 0xFF, 0xFF, 1, 0]);
+        assert_eq!(header[22..24], language.id.to_le_bytes());
+        let data = &resource[offset + 32..offset + 32 + data_size];
+        assert_eq!(message_table(&messages, &language.name), data);
+        offset += 32 + data_size.next_multiple_of(4);
+    }
+    assert_eq!(resource.len(), offset);
+}
+
+#[test]
+fn test_rust_source() {
+    let source = rust_source(&messages());
+
+    assert!(source.contains("pub(crate) const CATEGORY_COUNT: u32 = 4;\n"));
+    assert!(source.contains("pub const PANIC_CATEGORY: u16 = 0x00000002;\n"));
+    assert!(source.contains("pub const MSG_ERROR: u32 = 0xC0000100;\n"));
+    assert!(source.contains(r#""\"database events\"" => DATABASE_EVENTS_CATEGORY,"#));
+}
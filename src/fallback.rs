@@ -0,0 +1,203 @@
+use crate::{
+    error::{EventLogError, Result},
+    sink::{EventRecord, EventSink, OpenSink, try_lock},
+};
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::{
+    format::Writer,
+    time::{FormatTime, SystemTime},
+};
+
+/// Where events go while the event log can't be written to.
+pub struct Fallback {
+    kind: FallbackKind,
+}
+
+enum FallbackKind {
+    Stderr,
+    Writer(Mutex<Box<dyn Write + Send>>),
+}
+
+impl Fallback {
+    pub fn stderr() -> Self {
+        Self {
+            kind: FallbackKind::Stderr,
+        }
+    }
+
+    /// Writes to a file in `directory` that rotates daily.
+    pub fn rolling_file(
+        directory: impl AsRef<Path>,
+        file_name_prefix: impl Into<String>,
+    ) -> io::Result<Self> {
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(file_name_prefix.into())
+            .build(directory)
+            .map_err(io::Error::other)?;
+        Ok(Self::writer(appender))
+    }
+
+    pub fn writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            kind: FallbackKind::Writer(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    // Only waits for the writer's lock if `blocking` is set
+    fn write(&self, source: &str, record: &EventRecord, blocking: bool) -> io::Result<()> {
+        let mut line = String::new();
+        SystemTime
+            .format_time(&mut Writer::new(&mut line))
+            .map_err(io::Error::other)?;
+        line.push_str(&format!(
+            " {:>5} {source}: {}\n",
+            record.level,
+            record.message.trim_end()
+        ));
+
+        match &self.kind {
+            FallbackKind::Stderr => io::stderr().write_all(line.as_bytes()),
+            FallbackKind::Writer(writer) => {
+                let mut writer = if blocking {
+                    writer.lock().unwrap()
+                } else {
+                    try_lock(writer).map_err(|_| io::Error::from(io::ErrorKind::WouldBlock))?
+                };
+                writer.write_all(line.as_bytes())?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+struct State {
+    primary: Option<Box<dyn EventSink>>,
+    failures: u32,
+    using_fallback: bool,
+    last_attempt: Instant,
+}
+
+/// Writes to the event log and switches to a chain of fallbacks while it is unavailable.
+pub(crate) struct FallbackSink {
+    source: String,
+    open: OpenSink,
+    fallbacks: Vec<Fallback>,
+    failure_threshold: u32,
+    retry_interval: Duration,
+    state: Mutex<State>,
+}
+
+impl FallbackSink {
+    pub(crate) fn new(
+        source: String,
+        open: OpenSink,
+        fallbacks: Vec<Fallback>,
+        failure_threshold: u32,
+        retry_interval: Duration,
+    ) -> Self {
+        let primary = open().ok();
+        Self {
+            source,
+            open,
+            fallbacks,
+            failure_threshold: failure_threshold.max(1),
+            retry_interval,
+            state: Mutex::new(State {
+                using_fallback: primary.is_none(),
+                primary,
+                failures: 0,
+                last_attempt: Instant::now(),
+            }),
+        }
+    }
+
+    fn report_primary(
+        &self,
+        state: &mut State,
+        record: &EventRecord,
+        blocking: bool,
+    ) -> Result<()> {
+        state.last_attempt = Instant::now();
+        if state.primary.is_none() {
+            state.primary = Some((self.open)()?);
+        }
+        let primary = state
+            .primary
+            .as_ref()
+            .expect("primary sink was just opened");
+        let result = if blocking {
+            primary.report(record)
+        } else {
+            primary.try_report(record)
+        };
+        if let Err(e) = result {
+            state.failures += 1;
+            if state.failures >= self.failure_threshold {
+                state.using_fallback = true;
+            }
+            return Err(e);
+        }
+        state.failures = 0;
+        state.using_fallback = false;
+        Ok(())
+    }
+
+    fn report_fallback(
+        &self,
+        record: &EventRecord,
+        cause: EventLogError,
+        blocking: bool,
+    ) -> Result<()> {
+        let mut result = Err(cause);
+        for fallback in &self.fallbacks {
+            match fallback.write(&self.source, record, blocking) {
+                Ok(()) => return Ok(()),
+                Err(e) => result = Err(EventLogError::SystemError(e)),
+            }
+        }
+        result
+    }
+
+    fn report_locked(
+        &self,
+        mut state: MutexGuard<'_, State>,
+        record: &EventRecord,
+        blocking: bool,
+    ) -> Result<()> {
+        let cause = if !state.using_fallback || state.last_attempt.elapsed() >= self.retry_interval
+        {
+            match self.report_primary(&mut state, record, blocking) {
+                Ok(()) => return Ok(()),
+                // Still counts as a failure, but the event log writes the event once it is back
+                Err(EventLogError::Buffered) => return Err(EventLogError::Buffered),
+                Err(e) => e,
+            }
+        } else {
+            EventLogError::Unavailable
+        };
+        drop(state);
+
+        self.report_fallback(record, cause, blocking)
+    }
+}
+
+impl EventSink for FallbackSink {
+    fn report(&self, record: &EventRecord) -> Result<()> {
+        self.report_locked(self.state.lock().unwrap(), record, true)
+    }
+
+    fn try_report(&self, record: &EventRecord) -> Result<()> {
+        self.report_locked(try_lock(&self.state)?, record, false)
+    }
+}
+
+#[cfg(test)]
+#[path = "./fallback_test.rs"]
+mod fallback_test;
@@ -0,0 +1,194 @@
+use super::*;
+use crate::{sink::MockEventSink, test_support::SharedBuffer};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tracing::Level;
+
+struct BrokenWriter;
+
+impl Write for BrokenWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("broken"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn record(message: &str) -> EventRecord {
+    EventRecord::new(Level::INFO, 0, message)
+}
+
+fn failing_sink(calls: Arc<AtomicUsize>) -> MockEventSink {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(move |_| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err(EventLogError::Unavailable)
+    });
+    sink
+}
+
+#[test]
+fn test_falls_back_when_open_fails() {
+    let buffer = SharedBuffer::default();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(|| Err(EventLogError::Unavailable)),
+        vec![Fallback::writer(buffer.clone())],
+        3,
+        Duration::from_secs(60),
+    );
+
+    sink.report(&record("first")).unwrap();
+    sink.report(&record("second")).unwrap();
+
+    let lines = buffer.lines();
+    assert_eq!(2, lines.len());
+    assert!(lines[0].ends_with(" INFO Test Source: first"));
+    assert!(lines[1].ends_with(" INFO Test Source: second"));
+}
+
+#[test]
+fn test_switches_back_when_primary_recovers() {
+    let buffer = SharedBuffer::default();
+    let opened = Arc::new(AtomicUsize::new(0));
+    let open_count = opened.clone();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(move || {
+            // The first attempt happens during construction
+            if open_count.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(EventLogError::Unavailable);
+            }
+            let mut primary = MockEventSink::new();
+            primary
+                .expect_report()
+                .withf(|record| record.message == "recovered")
+                .returning(|_| Ok(()))
+                .once();
+            Ok(Box::new(primary))
+        }),
+        vec![Fallback::writer(buffer.clone())],
+        3,
+        Duration::ZERO,
+    );
+    assert_eq!(1, opened.load(Ordering::SeqCst));
+
+    sink.report(&record("recovered")).unwrap();
+
+    assert_eq!(2, opened.load(Ordering::SeqCst));
+    assert!(buffer.lines().is_empty());
+}
+
+#[test]
+fn test_switches_after_repeated_failures() {
+    let buffer = SharedBuffer::default();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let primary_calls = calls.clone();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(move || Ok(Box::new(failing_sink(primary_calls.clone())))),
+        vec![Fallback::writer(buffer.clone())],
+        2,
+        Duration::from_secs(60),
+    );
+
+    for i in 0..4 {
+        sink.report(&record(&format!("event {i}"))).unwrap();
+    }
+
+    // Once the threshold is reached the primary isn't retried until the interval elapses
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+    assert_eq!(4, buffer.lines().len());
+}
+
+#[test]
+fn test_buffered_events_count_as_failures() {
+    let buffer = SharedBuffer::default();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(|| {
+            let mut primary = MockEventSink::new();
+            primary
+                .expect_report()
+                .returning(|_| Err(EventLogError::Buffered));
+            Ok(Box::new(primary))
+        }),
+        vec![Fallback::writer(buffer.clone())],
+        2,
+        Duration::from_secs(60),
+    );
+
+    for i in 0..2 {
+        assert!(matches!(
+            sink.report(&record(&format!("buffered {i}"))),
+            Err(EventLogError::Buffered)
+        ));
+    }
+    sink.report(&record("fallback")).unwrap();
+
+    // Buffered events are written by the event log later, so only the last one falls back
+    let lines = buffer.lines();
+    assert_eq!(1, lines.len());
+    assert!(lines[0].ends_with(" INFO Test Source: fallback"));
+}
+
+#[test]
+fn test_uses_next_fallback_in_chain() {
+    let buffer = SharedBuffer::default();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(|| Err(EventLogError::Unavailable)),
+        vec![
+            Fallback::writer(BrokenWriter),
+            Fallback::writer(buffer.clone()),
+        ],
+        1,
+        Duration::from_secs(60),
+    );
+
+    sink.report(&record("event")).unwrap();
+
+    assert_eq!(1, buffer.lines().len());
+}
+
+#[test]
+fn test_errors_when_every_fallback_fails() {
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(|| Err(EventLogError::Unavailable)),
+        vec![Fallback::writer(BrokenWriter)],
+        1,
+        Duration::from_secs(60),
+    );
+
+    assert!(matches!(
+        sink.report(&record("event")),
+        Err(EventLogError::SystemError(_))
+    ));
+}
+
+#[test]
+fn test_try_report_does_not_wait_for_lock() {
+    let buffer = SharedBuffer::default();
+    let sink = FallbackSink::new(
+        "Test Source".to_owned(),
+        Box::new(|| Err(EventLogError::Unavailable)),
+        vec![Fallback::writer(buffer.clone())],
+        1,
+        Duration::from_secs(60),
+    );
+
+    let state = sink.state.lock().unwrap();
+    assert!(matches!(
+        sink.try_report(&record("event")),
+        Err(EventLogError::Busy)
+    ));
+    drop(state);
+
+    sink.try_report(&record("event")).unwrap();
+    assert_eq!(1, buffer.lines().len());
+}
@@ -0,0 +1,208 @@
+use crate::redact::{RedactingVisitor, Redactions, current_target, glob_match};
+use std::{
+    fmt::{self, Debug, Write},
+    sync::Arc,
+};
+use tracing_core::field::{Field, Visit};
+use tracing_subscriber::{
+    field::{MakeVisitor, VisitFmt},
+    fmt::format::{DefaultFields, FieldFn, Pretty, PrettyFields, Writer},
+};
+
+/// A field formatter that [`EventLogLayer`](crate::EventLogLayer) can apply its field filter
+/// and redaction rules to.
+///
+/// The layer hides and replaces fields one at a time before they reach the formatter, so the
+/// formatter has to be built from a [`MakeVisitor`]. This is implemented for [`DefaultFields`],
+/// [`PrettyFields`], [`Pretty`] and formatters made with
+/// [`debug_fn`](tracing_subscriber::fmt::format::debug_fn). A custom formatter can be used by
+/// implementing this trait with a visitor producing its output. Formatters that only implement
+/// `FormatFields`, such as `JsonFields`, aren't supported.
+pub trait EventLogFieldFormat: 'static {
+    /// Visitor-based formatter producing the same output.
+    type Fields: for<'writer> MakeVisitor<Writer<'writer>, Visitor: VisitFmt> + 'static;
+
+    fn into_fields(self) -> Self::Fields;
+}
+
+impl EventLogFieldFormat for DefaultFields {
+    type Fields = Self;
+
+    fn into_fields(self) -> Self::Fields {
+        self
+    }
+}
+
+impl EventLogFieldFormat for PrettyFields {
+    type Fields = Self;
+
+    fn into_fields(self) -> Self::Fields {
+        self
+    }
+}
+
+impl EventLogFieldFormat for Pretty {
+    type Fields = PrettyFields;
+
+    fn into_fields(self) -> Self::Fields {
+        PrettyFields::new()
+    }
+}
+
+impl<F> EventLogFieldFormat for FieldFn<F>
+where
+    F: for<'writer> Fn(&mut Writer<'writer>, &Field, &dyn Debug) -> fmt::Result + Clone + 'static,
+{
+    type Fields = Self;
+
+    fn into_fields(self) -> Self::Fields {
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+enum FieldMatcher {
+    Name(String),
+    Target(String),
+}
+
+impl FieldMatcher {
+    fn matches(&self, target: &str, field: &str) -> bool {
+        match self {
+            FieldMatcher::Name(pattern) => glob_match(pattern, field),
+            FieldMatcher::Target(pattern) => glob_match(pattern, target),
+        }
+    }
+}
+
+/// Selects which fields are written to the event log. Other layers still see every field.
+///
+/// A field is written if it matches an allow rule and no deny rule. Patterns are globs where `*`
+/// matches any number of characters and `?` matches one. The default only allows `message`,
+/// `error` and `category`.
+#[derive(Clone, Debug)]
+pub struct FieldFilter {
+    allow: Vec<FieldMatcher>,
+    deny: Vec<FieldMatcher>,
+}
+
+impl Default for FieldFilter {
+    fn default() -> Self {
+        Self::none()
+            .allow_field("message")
+            .allow_field("error")
+            .allow_field("category")
+    }
+}
+
+impl FieldFilter {
+    /// Allows every field.
+    pub fn all() -> Self {
+        Self::none().allow_field("*")
+    }
+
+    /// Allows no fields until allow rules are added.
+    pub fn none() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+
+    pub fn allow_field<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.allow.push(FieldMatcher::Name(pattern.into()));
+        self
+    }
+
+    pub fn deny_field<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.deny.push(FieldMatcher::Name(pattern.into()));
+        self
+    }
+
+    /// Allows every field of events and spans whose target matches `pattern`.
+    pub fn allow_target<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.allow.push(FieldMatcher::Target(pattern.into()));
+        self
+    }
+
+    /// Denies every field of events and spans whose target matches `pattern`. Events from these
+    /// targets aren't written at all and count as
+    /// [`DropReason::Filtered`](crate::DropReason::Filtered).
+    pub fn deny_target<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.deny.push(FieldMatcher::Target(pattern.into()));
+        self
+    }
+
+    pub(crate) fn denies_target(&self, target: &str) -> bool {
+        self.deny
+            .iter()
+            .any(|m| matches!(m, FieldMatcher::Target(pattern) if glob_match(pattern, target)))
+    }
+
+    pub(crate) fn allows(&self, target: &str, field: &str) -> bool {
+        self.allow.iter().any(|m| m.matches(target, field))
+            && !self.deny.iter().any(|m| m.matches(target, field))
+    }
+}
+
+/// Field formatter used by [`EventLogLayer`](crate::EventLogLayer), applying the layer's
+/// field filter and redaction rules before passing fields to the configured formatter `N`.
+///
+/// Span fields are formatted separately from other `fmt` layers, so neither affects their
+/// output.
+pub struct EventLogFields<N: EventLogFieldFormat> {
+    inner: N::Fields,
+    filter: Arc<FieldFilter>,
+    redactions: Redactions,
+}
+
+impl<N: EventLogFieldFormat> EventLogFields<N> {
+    pub(crate) fn new(inner: N, filter: Arc<FieldFilter>, redactions: Redactions) -> Self {
+        Self {
+            inner: inner.into_fields(),
+            filter,
+            redactions,
+        }
+    }
+}
+
+impl<'a, N: EventLogFieldFormat> MakeVisitor<Writer<'a>> for EventLogFields<N> {
+    type Visitor = RedactingVisitor<<N::Fields as MakeVisitor<Writer<'a>>>::Visitor>;
+
+    fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
+        RedactingVisitor::new(
+            self.inner.make_visitor(target),
+            self.filter.clone(),
+            self.redactions.clone(),
+            current_target(),
+        )
+    }
+}
+
+/// Renders event fields other than `message` as `name=value` lines for raw data.
+#[derive(Default)]
+pub(crate) struct RawDataVisitor(String);
+
+impl RawDataVisitor {
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.0.into_bytes()
+    }
+}
+
+impl Visit for RawDataVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() != "message" {
+            let _ = writeln!(self.0, "{}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() != "message" {
+            let _ = writeln!(self.0, "{}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./fields_test.rs"]
+mod fields_test;
@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn test_default_filter() {
+    let filter = FieldFilter::default();
+
+    assert!(filter.allows("app", "message"));
+    assert!(filter.allows("app", "error"));
+    assert!(filter.allows("app", "category"));
+    assert!(!filter.allows("app", "request_id"));
+}
+
+#[test]
+fn test_allow_and_deny() {
+    let filter = FieldFilter::all()
+        .deny_field("debug_*")
+        .deny_target("my_app::noisy*");
+
+    assert!(filter.allows("my_app", "user"));
+    assert!(!filter.allows("my_app", "debug_state"));
+    assert!(!filter.allows("my_app::noisy::module", "user"));
+
+    let filter = FieldFilter::default().allow_target("my_app::audit");
+
+    assert!(filter.allows("my_app::audit", "user"));
+    assert!(!filter.allows("my_app", "user"));
+}
@@ -1,25 +1,58 @@
 use error::Result;
-#[cfg_attr(test, double)]
 #[cfg(windows)]
 use eventlog::EventLog;
-#[cfg(test)]
-use mockall_double::double;
+use std::fmt::Debug;
 use std::io;
-use std::{ffi::OsStr, fmt::Debug, sync::Mutex};
-use tracing::{Metadata, Subscriber, span};
+use std::sync::{Arc, Mutex};
+use tracing::{Metadata, Subscriber, level_filters::LevelFilter, span};
 use tracing_core::{Event, Field};
 use tracing_subscriber::fmt::format::{Compact, DefaultFields, Format, Pretty};
-use tracing_subscriber::fmt::{FormatEvent, FormatFields, Layer, MakeWriter};
+use tracing_subscriber::fmt::{FormatEvent, Layer, MakeWriter};
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
-use widestring::WideCString;
 
+mod builder;
+#[cfg(feature = "serde")]
+mod config;
 #[cfg(windows)]
 mod eventlog;
+#[cfg_attr(not(windows), allow(dead_code))]
 mod eventmsgs;
+mod fallback;
+mod fields;
+mod lifecycle;
+#[cfg(feature = "log")]
+mod logger;
+mod mapping;
+mod panic_hook;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod reconnect;
+mod redact;
 mod registry;
-pub use self::registry::platform::*;
+mod reporter;
+mod sink;
+mod stats;
+#[cfg(test)]
+mod test_support;
+pub use self::builder::EventLogLayerBuilder;
+#[cfg(feature = "serde")]
+pub use self::config::{
+    EventLogConfig, FilterBase, FilterConfig, FormatConfig, LogConfig, MessageFilesConfig,
+    RetentionConfig,
+};
+pub use self::fallback::Fallback;
+pub use self::fields::{EventLogFieldFormat, EventLogFields, FieldFilter};
+pub use self::lifecycle::LifecycleGuard;
+#[cfg(feature = "log")]
+pub use self::logger::EventLogLogger;
+pub use self::redact::{RedactAction, RedactionRule};
 pub use self::registry::*;
+pub use self::sink::{EventRecord, EventSink};
+pub use self::stats::{DropReason, EventLogStats, StatsSnapshot};
+use fields::RawDataVisitor;
+use mapping::EventMapping;
+use redact::{RedactingVisitor, Redactions};
+use reporter::Reporter;
 
 pub mod error;
 
@@ -30,36 +63,70 @@ thread_local! {
 pub struct EventLogLayer<S, N, F>
 where
     S: Subscriber + for<'span> LookupSpan<'span>,
-    N: for<'writer> FormatFields<'writer> + 'static,
-    F: FormatEvent<S, N>,
+    N: EventLogFieldFormat,
+    F: FormatEvent<S, EventLogFields<N>>,
 {
-    #[cfg(windows)]
-    event_log: EventLog,
-    inner: Layer<S, N, F, MemWriter>,
+    // Only missing on platforms without an event log when no fallback is configured
+    reporter: Option<Reporter>,
+    stats: EventLogStats,
+    lifecycle: Option<LifecycleGuard>,
+    field_filter: Arc<FieldFilter>,
+    redactions: Redactions,
+    raw_data: bool,
+    max_level: LevelFilter,
+    mapping: EventMapping,
+    inner: Layer<S, EventLogFields<N>, F, MemWriter>,
 }
 
 impl<S, N, F> EventLogLayer<S, N, F>
 where
     S: Subscriber + for<'span> LookupSpan<'span>,
-    N: for<'writer> FormatFields<'writer> + 'static,
-    F: FormatEvent<S, N>,
+    N: EventLogFieldFormat,
+    F: FormatEvent<S, EventLogFields<N>>,
 {
     pub fn new<T: Into<String> + 'static>(source: T, inner: Layer<S, N, F>) -> Result<Self> {
-        #[cfg(windows)]
-        let event_log = EventLog::new(source)?;
-
-        let inner = inner.with_writer(MemWriter {});
-        Ok(Self {
-            inner,
-            #[cfg(windows)]
-            event_log,
-        })
+        EventLogLayerBuilder::new(source).build(inner)
+    }
+
+    /// Returns a handle to this layer's statistics.
+    pub fn stats(&self) -> EventLogStats {
+        self.stats.clone()
+    }
+
+    /// Takes the guard that writes the shutdown event.
+    ///
+    /// Returns `None` if lifecycle events are disabled or the guard was already taken. A guard
+    /// that is never taken writes the shutdown event when the layer is dropped.
+    pub fn lifecycle_guard(&mut self) -> Option<LifecycleGuard> {
+        self.lifecycle.take()
+    }
+
+    /// Records panics through this layer's sink before running the previously installed panic
+    /// hook.
+    ///
+    /// The event uses its own message ID and category and is counted in the layer's statistics.
+    /// A backtrace is included when enabled with `RUST_BACKTRACE`. Panics are written to stderr
+    /// instead if the sink is busy, e.g. because the panic happened while writing an event.
+    pub fn install_panic_hook(&self) {
+        if let Some(reporter) = &self.reporter {
+            panic_hook::install(reporter.clone());
+        }
+    }
+
+    /// A `log` logger writing through this layer's sink, with the same level and category
+    /// mappings and statistics.
+    #[cfg(feature = "log")]
+    pub fn logger(&self) -> EventLogLogger {
+        EventLogLogger::from_parts(
+            self.reporter.clone(),
+            self.stats.clone(),
+            self.mapping.clone(),
+        )
     }
 
-    #[cfg(all(windows, test))]
-    fn from_event_log(event_log: EventLog, inner: Layer<S, N, F>) -> Self {
-        let inner = inner.with_writer(MemWriter {});
-        Self { inner, event_log }
+    #[cfg(test)]
+    fn from_sink(sink: impl EventSink + 'static, inner: Layer<S, N, F>) -> Self {
+        EventLogLayerBuilder::new("test").build_with_test_sink(sink, inner)
     }
 }
 
@@ -68,14 +135,7 @@ where
     S: Subscriber + for<'span> LookupSpan<'span>,
 {
     pub fn pretty<T: Into<String> + 'static>(source: T) -> Result<Self> {
-        Self::new(
-            source,
-            tracing_subscriber::fmt::layer()
-                .pretty()
-                .with_ansi(false)
-                .without_time()
-                .with_level(false),
-        )
+        EventLogLayerBuilder::new(source).pretty()
     }
 }
 
@@ -84,35 +144,24 @@ where
     S: Subscriber + for<'span> LookupSpan<'span>,
 {
     pub fn compact<T: Into<String> + 'static>(source: T) -> Result<Self> {
-        Self::new(
-            source,
-            tracing_subscriber::fmt::layer()
-                .compact()
-                .with_ansi(false)
-                .without_time()
-                .with_level(false),
-        )
+        EventLogLayerBuilder::new(source).compact()
     }
 }
 
 impl<S, N, F> tracing_subscriber::Layer<S> for EventLogLayer<S, N, F>
 where
     S: Subscriber + for<'span> LookupSpan<'span>,
-    N: for<'writer> FormatFields<'writer> + 'static,
-    F: FormatEvent<S, N> + 'static,
+    N: EventLogFieldFormat,
+    F: FormatEvent<S, EventLogFields<N>> + 'static,
 {
-    #[cfg(windows)]
     fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
         self.inner.enabled(metadata, ctx)
     }
 
-    #[cfg(not(windows))]
-    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
-        false
-    }
-
     fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
-        self.inner.on_new_span(attrs, id, ctx)
+        redact::with_target(attrs.metadata().target(), || {
+            self.inner.on_new_span(attrs, id, ctx)
+        })
     }
 
     fn on_record(
@@ -121,12 +170,24 @@ where
         values: &tracing_core::span::Record<'_>,
         ctx: Context<'_, S>,
     ) {
-        self.inner.on_record(span, values, ctx)
+        let target = ctx
+            .span(span)
+            .map(|span| span.metadata().target())
+            .unwrap_or_default();
+        redact::with_target(target, || self.inner.on_record(span, values, ctx))
     }
 
-    #[cfg(windows)]
     fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
-        self.inner.on_event(event, ctx);
+        let Some(reporter) = &self.reporter else {
+            self.stats.record_dropped(DropReason::Unavailable);
+            return;
+        };
+        let target = event.metadata().target();
+        if *event.metadata().level() > self.max_level || self.field_filter.denies_target(target) {
+            self.stats.record_dropped(DropReason::Filtered);
+            return;
+        }
+        redact::with_target(target, || self.inner.on_event(event, ctx));
 
         let mut category = "".to_owned();
         let mut visitor = |field: &Field, value: &dyn Debug| {
@@ -135,21 +196,34 @@ where
             }
         };
         event.record(&mut visitor);
-        let field = BUFFER.with(|buffer| {
+        let message = BUFFER.with(|buffer| {
             let mut data = buffer.lock().unwrap();
-            let str_data = std::str::from_utf8(data.as_slice()).unwrap();
-            let c_str = WideCString::from_os_str(OsStr::new(str_data)).unwrap();
+            let message = String::from_utf8_lossy(data.as_slice()).into_owned();
             data.clear();
-            c_str
+            message
         });
 
-        self.event_log
-            .report_event(
-                *event.metadata().level(),
-                eventmsgs::get_category(category),
-                field,
+        let raw_data = if self.raw_data {
+            let mut visitor = RedactingVisitor::new(
+                RawDataVisitor::default(),
+                self.field_filter.clone(),
+                self.redactions.clone(),
+                target,
+            );
+            event.record(&mut visitor);
+            visitor.into_inner().into_bytes()
+        } else {
+            Vec::new()
+        };
+
+        reporter.report(EventRecord {
+            raw_data,
+            ..EventRecord::new(
+                self.mapping.level(*event.metadata().level()),
+                self.mapping.category(category),
+                message,
             )
-            .unwrap();
+        });
     }
 
     fn on_enter(&self, id: &tracing_core::span::Id, ctx: Context<'_, S>) {
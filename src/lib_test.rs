@@ -1,16 +1,13 @@
 use super::*;
+use crate::sink::MockEventSink;
 use tracing::{Level, info};
 use tracing_subscriber::layer::SubscriberExt;
 
-#[cfg(windows)]
 #[test]
 fn test() {
-    let mut event_log = EventLog::default();
-    event_log
-        .expect_report_event()
-        .returning(|_: Level, _, _| Ok(()))
-        .once();
-    let layer = EventLogLayer::from_event_log(event_log, tracing_subscriber::fmt::layer().pretty());
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(|_| Ok(())).once();
+    let layer = EventLogLayer::from_sink(sink, tracing_subscriber::fmt::layer().pretty());
 
     let reg = tracing_subscriber::registry().with(layer);
     let _guard = tracing::subscriber::set_default(reg);
@@ -22,8 +19,284 @@ fn test() {
 fn test_can_run() {
     // Ensure this can run without erroring on non-Windows targets
     let layer = EventLogLayer::pretty("test").unwrap();
+    let stats = layer.stats();
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    info!("test log");
+    assert_eq!(1, stats.snapshot().unavailable);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_fallback_without_event_log() {
+    use crate::test_support::SharedBuffer;
+
+    let buffer = SharedBuffer::default();
+    let layer = EventLogLayerBuilder::new("test")
+        .fallback(Fallback::writer(buffer.clone()))
+        .compact()
+        .unwrap();
 
     let reg = tracing_subscriber::registry().with(layer);
     let _guard = tracing::subscriber::set_default(reg);
     info!("test log");
+
+    let output = buffer.contents();
+    assert!(
+        output.ends_with(" INFO test: tracing_eventlog::lib_test: test log\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_stats() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(|_| Ok(())).once();
+    let layer = EventLogLayer::from_sink(sink, tracing_subscriber::fmt::layer().compact());
+    let stats = layer.stats();
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    info!("test log");
+
+    let snapshot = stats.snapshot();
+    assert_eq!(1, snapshot.written);
+    assert_eq!(0, snapshot.failed_total());
+}
+
+fn redacting_layer(
+    sink: MockEventSink,
+) -> EventLogLayer<tracing_subscriber::Registry, DefaultFields, Format<Compact, ()>> {
+    EventLogLayerBuilder::new("test")
+        .field_filter(FieldFilter::all())
+        .redact(RedactionRule::field("token", RedactAction::Mask))
+        .redact(RedactionRule::value(r"\w+@example\.com", RedactAction::Hash).unwrap())
+        .redact(RedactionRule::field("password", RedactAction::Drop))
+        .raw_data(true)
+        .build_with_test_sink(
+            sink,
+            tracing_subscriber::fmt::layer()
+                .compact()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false),
+        )
+}
+
+#[test]
+fn test_redacts_insertion_strings_and_raw_data() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            let raw_data = String::from_utf8(record.raw_data.clone()).unwrap();
+            record
+                .message
+                .ends_with("login token=\"***\" user=\"sha256:8c87b489ce35cf2e\"\n")
+                && !record.message.contains("hunter2")
+                && raw_data == "token=***\nuser=sha256:8c87b489ce35cf2e\n"
+        })
+        .returning(|_| Ok(()))
+        .once();
+
+    let reg = tracing_subscriber::registry().with(redacting_layer(sink));
+    let _guard = tracing::subscriber::set_default(reg);
+    info!(
+        token = "secret",
+        user = "jane@example.com",
+        password = "hunter2",
+        "login"
+    );
+}
+
+#[test]
+fn test_redacts_span_fields() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.message.contains("token=\"***\"")
+                && record
+                    .message
+                    .contains("session=\"sha256:8c87b489ce35cf2e\"")
+        })
+        .returning(|_| Ok(()))
+        .once();
+
+    let reg = tracing_subscriber::registry().with(redacting_layer(sink));
+    let _guard = tracing::subscriber::set_default(reg);
+    let span = tracing::info_span!("request", token = "secret", session = tracing::field::Empty);
+    span.record("session", "jane@example.com");
+    let _entered = span.enter();
+    info!("handled");
+}
+
+#[test]
+fn test_field_filter() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.message == "request: tracing_eventlog::lib_test: failed error=\"timeout\" category=\"Network Events\"\n"
+                && record.category == eventmsgs::NETWORK_EVENTS_CATEGORY
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let layer = EventLogLayerBuilder::new("test")
+        .field_filter(FieldFilter::default())
+        .build_with_test_sink(
+            sink,
+            tracing_subscriber::fmt::layer()
+                .compact()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false),
+        );
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    let _entered = tracing::info_span!("request", request_id = 42).entered();
+    info!(
+        error = "timeout",
+        category = "Network Events",
+        attempt = 3,
+        "failed"
+    );
+}
+
+#[test]
+fn test_custom_field_format() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record
+                .message
+                .ends_with("message=failed;error=\"timeout\";\n")
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let fields = tracing_subscriber::fmt::format::debug_fn(|writer, field, value| {
+        write!(writer, "{}={value:?};", field.name())
+    });
+    let layer = EventLogLayerBuilder::new("test")
+        .field_filter(FieldFilter::default())
+        .build_with_test_sink(
+            sink,
+            tracing_subscriber::fmt::layer()
+                .fmt_fields(fields)
+                .compact()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false),
+        );
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    info!(error = "timeout", attempt = 3, "failed");
+}
+
+#[test]
+fn test_level_and_category_mapping() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.level == Level::ERROR
+                && record.event_id == eventmsgs::MSG_ERROR
+                && record.category == eventmsgs::DATABASE_EVENTS_CATEGORY
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let layer = EventLogLayerBuilder::new("test")
+        .map_level(Level::WARN, Level::ERROR)
+        .map_category("DB", "database events")
+        .build_with_test_sink(sink, tracing_subscriber::fmt::layer().compact());
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    tracing::warn!(category = "db", "slow query");
+}
+
+#[test]
+fn test_unknown_category() {
+    let err = EventLogLayerBuilder::new("test")
+        .map_category("db", "Storage")
+        .compact::<tracing_subscriber::Registry>()
+        .err()
+        .unwrap();
+
+    assert!(
+        matches!(&err, error::EventLogError::UnknownCategory(category) if category == "Storage"),
+        "{err}"
+    );
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_logger_shares_the_layer_pipeline() {
+    use log::Log;
+
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| record.level == Level::ERROR && record.message == "app: disk almost full")
+        .returning(|_| Ok(()))
+        .once();
+    let layer = EventLogLayerBuilder::new("test")
+        .map_level(Level::WARN, Level::ERROR)
+        .build_with_test_sink(
+            sink,
+            tracing_subscriber::fmt::layer::<tracing_subscriber::Registry>().compact(),
+        );
+    let logger = layer.logger();
+
+    logger.log(
+        &log::Record::builder()
+            .level(log::Level::Warn)
+            .target("app")
+            .args(format_args!("disk almost full"))
+            .build(),
+    );
+    assert_eq!(1, layer.stats().snapshot().written);
+}
+
+#[test]
+fn test_remote_auto_register_needs_message_files() {
+    let err = EventLogLayerBuilder::new("test")
+        .server("logs01")
+        .auto_register(true)
+        .compact::<tracing_subscriber::Registry>()
+        .err()
+        .unwrap();
+
+    assert!(
+        matches!(
+            &err,
+            error::EventLogError::RegistryError(error::RegistryError::RemoteMessageFilesRequired(
+                server
+            ))
+                if server == "logs01"
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_counts_filtered_events() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(|_| Ok(())).once();
+    let layer = EventLogLayerBuilder::new("test")
+        .max_level(LevelFilter::INFO)
+        .field_filter(FieldFilter::default().deny_target("noisy*"))
+        .build_with_test_sink(
+            sink,
+            tracing_subscriber::fmt::layer::<tracing_subscriber::Registry>().compact(),
+        );
+    let stats = layer.stats();
+
+    let reg = tracing_subscriber::registry().with(layer);
+    let _guard = tracing::subscriber::set_default(reg);
+    tracing::debug!("too verbose");
+    info!(target: "noisy::poller", "denied target");
+    info!("written");
+
+    let snapshot = stats.snapshot();
+    assert_eq!(1, snapshot.written);
+    assert_eq!(2, snapshot.filtered);
 }
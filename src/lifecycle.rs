@@ -0,0 +1,72 @@
+use crate::{eventmsgs, reporter::Reporter, sink::EventRecord, stats::EventLogStats};
+use std::{env, time::Instant};
+use tracing::Level;
+
+/// Writes the shutdown event when dropped.
+///
+/// Returned by [`EventLogLayer::lifecycle_guard`](crate::EventLogLayer::lifecycle_guard) when
+/// lifecycle events are enabled. Keep it alive until the process is about to exit.
+pub struct LifecycleGuard {
+    reporter: Reporter,
+    stats: EventLogStats,
+    started: Instant,
+}
+
+impl LifecycleGuard {
+    /// Writes the startup event and returns a guard for the matching shutdown event.
+    pub(crate) fn start(
+        reporter: Reporter,
+        stats: EventLogStats,
+        source: &str,
+        version: Option<&str>,
+    ) -> Self {
+        let exe_path = env::current_exe()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "<unknown>".to_owned());
+        let command_line = env::args().collect::<Vec<_>>().join(" ");
+        let message = format!(
+            "Process started.\n\nVersion: {}\nPID: {}\nExecutable: {exe_path}\n\
+            Command line: {command_line}\nSource: {source}",
+            version.unwrap_or("<unknown>"),
+            std::process::id(),
+        );
+        reporter.report(lifecycle_record(
+            eventmsgs::MSG_STARTUP,
+            eventmsgs::STARTUP_CATEGORY,
+            message,
+        ));
+
+        Self {
+            reporter,
+            stats,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for LifecycleGuard {
+    fn drop(&mut self) {
+        let message = format!(
+            "Process stopped.\n\nPID: {}\nUptime: {:.3?}\nEvents: {}",
+            std::process::id(),
+            self.started.elapsed(),
+            self.stats.snapshot(),
+        );
+        self.reporter.report(lifecycle_record(
+            eventmsgs::MSG_SHUTDOWN,
+            eventmsgs::SHUTDOWN_CATEGORY,
+            message,
+        ));
+    }
+}
+
+fn lifecycle_record(event_id: u32, category: u16, message: String) -> EventRecord {
+    EventRecord {
+        event_id,
+        ..EventRecord::new(Level::INFO, category, message)
+    }
+}
+
+#[cfg(test)]
+#[path = "./lifecycle_test.rs"]
+mod lifecycle_test;
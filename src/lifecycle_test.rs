@@ -0,0 +1,37 @@
+use super::*;
+use crate::sink::MockEventSink;
+use mockall::Sequence;
+use std::sync::Arc;
+
+#[test]
+fn test_startup_and_shutdown_events() {
+    let mut sink = MockEventSink::new();
+    let mut seq = Sequence::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.event_id == eventmsgs::MSG_STARTUP
+                && record.category == eventmsgs::STARTUP_CATEGORY
+                && record
+                    .message
+                    .starts_with("Process started.\n\nVersion: 1.2.3\nPID: ")
+                && record.message.ends_with("\nSource: Test Source")
+        })
+        .returning(|_| Ok(()))
+        .once()
+        .in_sequence(&mut seq);
+    sink.expect_report()
+        .withf(|record| {
+            record.event_id == eventmsgs::MSG_SHUTDOWN
+                && record.category == eventmsgs::SHUTDOWN_CATEGORY
+                && record.message.contains("\nUptime: ")
+                && record.message.contains("\nEvents: written=1 ")
+        })
+        .returning(|_| Ok(()))
+        .once()
+        .in_sequence(&mut seq);
+    let stats = EventLogStats::new();
+    let reporter = Reporter::new(Arc::new(sink), stats.clone(), None);
+
+    let guard = LifecycleGuard::start(reporter, stats, "Test Source", Some("1.2.3"));
+    drop(guard);
+}
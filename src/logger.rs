@@ -0,0 +1,124 @@
+#[cfg(test)]
+use crate::sink::EventSink;
+use crate::{
+    EventLogLayerBuilder,
+    error::Result,
+    mapping::EventMapping,
+    reporter::Reporter,
+    sink::EventRecord,
+    stats::{DropReason, EventLogStats},
+};
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// A [`log::Log`] implementation that writes records to the Windows event log.
+///
+/// Records go through the same level and category mappings, message IDs and sink as
+/// [`EventLogLayer`](crate::EventLogLayer): get one from
+/// [`EventLogLayer::logger`](crate::EventLogLayer::logger) to share the layer's sink, or from
+/// [`EventLogLayerBuilder::build_logger`]. A category can be attached with the `category` key,
+/// e.g. `log::info!(category = "Database Events"; "connected")`.
+pub struct EventLogLogger {
+    reporter: Option<Reporter>,
+    stats: EventLogStats,
+    mapping: EventMapping,
+    max_level: LevelFilter,
+}
+
+impl EventLogLogger {
+    pub fn new<T: Into<String> + 'static>(source: T) -> Result<Self> {
+        EventLogLayerBuilder::new(source).build_logger()
+    }
+
+    pub(crate) fn from_parts(
+        reporter: Option<Reporter>,
+        stats: EventLogStats,
+        mapping: EventMapping,
+    ) -> Self {
+        Self {
+            reporter,
+            stats,
+            mapping,
+            max_level: LevelFilter::Trace,
+        }
+    }
+
+    #[cfg(test)]
+    fn from_sink(sink: impl EventSink + 'static, mapping: EventMapping) -> Self {
+        let stats = EventLogStats::new();
+        let reporter = Reporter::new(std::sync::Arc::new(sink), stats.clone(), None);
+        Self::from_parts(Some(reporter), stats, mapping)
+    }
+
+    /// Records statistics into an existing handle, e.g. one shared with an
+    /// [`EventLogLayer`](crate::EventLogLayer).
+    pub fn with_stats(mut self, stats: EventLogStats) -> Self {
+        self.reporter = self.reporter.map(|r| r.with_stats(stats.clone()));
+        self.stats = stats;
+        self
+    }
+
+    pub fn stats(&self) -> EventLogStats {
+        self.stats.clone()
+    }
+
+    pub fn with_max_level(mut self, max_level: LevelFilter) -> Self {
+        self.max_level = max_level;
+        self
+    }
+
+    /// Installs this logger as the global `log` logger.
+    pub fn init(self) -> core::result::Result<(), SetLoggerError> {
+        let max_level = self.max_level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+fn tracing_level(level: log::Level) -> tracing::Level {
+    match level {
+        log::Level::Error => tracing::Level::ERROR,
+        log::Level::Warn => tracing::Level::WARN,
+        log::Level::Info => tracing::Level::INFO,
+        log::Level::Debug => tracing::Level::DEBUG,
+        log::Level::Trace => tracing::Level::TRACE,
+    }
+}
+
+fn category(record: &Record<'_>) -> String {
+    // Format the value the same way the tracing visitor does so both share one mapping
+    record
+        .key_values()
+        .get(log::kv::Key::from_str("category"))
+        .map(|value| format!("{value:?}"))
+        .unwrap_or_default()
+}
+
+impl Log for EventLogLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.reporter.is_some() && metadata.level() <= self.max_level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if record.level() > self.max_level {
+            self.stats.record_dropped(DropReason::Filtered);
+            return;
+        }
+        let Some(reporter) = &self.reporter else {
+            self.stats.record_dropped(DropReason::Unavailable);
+            return;
+        };
+
+        reporter.report(EventRecord::new(
+            self.mapping.level(tracing_level(record.level())),
+            self.mapping.category(category(record)),
+            format!("{}: {}", record.target(), record.args()),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+#[path = "./logger_test.rs"]
+mod logger_test;
@@ -0,0 +1,85 @@
+use super::*;
+use crate::eventmsgs;
+use crate::sink::MockEventSink;
+use log::{Level, Record};
+
+#[test]
+fn test_log() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.level == tracing::Level::WARN
+                && record.event_id == eventmsgs::MSG_WARNING
+                && record.category == eventmsgs::DATABASE_EVENTS_CATEGORY
+                && record.message == "test_target: test log"
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let logger = EventLogLogger::from_sink(sink, EventMapping::default());
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Warn)
+            .target("test_target")
+            .args(format_args!("test log"))
+            .key_values(&[("category", "Database Events")])
+            .build(),
+    );
+}
+
+#[test]
+fn test_max_level() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(|_| Ok(())).never();
+    let logger = EventLogLogger::from_sink(sink, EventMapping::default())
+        .with_max_level(log::LevelFilter::Info);
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Debug)
+            .args(format_args!("test log"))
+            .build(),
+    );
+    assert_eq!(1, logger.stats().snapshot().filtered);
+}
+
+#[test]
+fn test_mappings() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.level == tracing::Level::ERROR
+                && record.event_id == eventmsgs::MSG_ERROR
+                && record.category == eventmsgs::NETWORK_EVENTS_CATEGORY
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let mapping = EventMapping::new(
+        &[(tracing::Level::WARN, tracing::Level::ERROR)],
+        &[("net".to_owned(), "Network Events".to_owned())],
+    )
+    .unwrap();
+    let logger = EventLogLogger::from_sink(sink, mapping);
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Warn)
+            .args(format_args!("connection reset"))
+            .key_values(&[("category", "net")])
+            .build(),
+    );
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_can_run() {
+    let logger = EventLogLogger::new("test").unwrap();
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Info)
+            .args(format_args!("test log"))
+            .build(),
+    );
+    assert_eq!(1, logger.stats().snapshot().unavailable);
+}
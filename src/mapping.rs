@@ -0,0 +1,52 @@
+use crate::{
+    error::{EventLogError, Result},
+    eventmsgs,
+};
+use tracing::Level;
+
+/// The levels and categories events are written with, shared by
+/// [`EventLogLayer`](crate::EventLogLayer) and the `log` bridge.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventMapping {
+    levels: Vec<(Level, Level)>,
+    // Debug formatted `category` values and the categories they are written in
+    categories: Vec<(String, u16)>,
+}
+
+impl EventMapping {
+    /// Fails if one of the categories isn't compiled into the message table.
+    pub(crate) fn new(levels: &[(Level, Level)], categories: &[(String, String)]) -> Result<Self> {
+        let categories = categories
+            .iter()
+            .map(
+                |(value, category)| match eventmsgs::find_category(category) {
+                    Some(id) => Ok((format!("{value:?}"), id)),
+                    None => Err(EventLogError::UnknownCategory(category.clone())),
+                },
+            )
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            levels: levels.to_vec(),
+            categories,
+        })
+    }
+
+    pub(crate) fn level(&self, level: Level) -> Level {
+        self.levels
+            .iter()
+            .find(|&&(from, _)| from == level)
+            .map_or(level, |&(_, to)| to)
+    }
+
+    /// The category of an event whose `category` field formats as `value` with Debug.
+    pub(crate) fn category(&self, value: String) -> u16 {
+        match self
+            .categories
+            .iter()
+            .find(|(mapped, _)| mapped.eq_ignore_ascii_case(value.trim()))
+        {
+            Some(&(_, id)) => id,
+            None => eventmsgs::get_category(value),
+        }
+    }
+}
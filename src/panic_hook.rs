@@ -0,0 +1,68 @@
+use crate::{eventmsgs, reporter::Reporter, sink::EventRecord};
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    panic::{self, Location},
+    thread,
+};
+use tracing::Level;
+
+/// Records panics with `reporter` before running the previously installed panic hook.
+pub(crate) fn install(reporter: Reporter) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let message = panic_message(
+            payload_str(info.payload()),
+            info.location(),
+            thread::current().name(),
+            &Backtrace::capture(),
+        );
+        report_panic(&reporter, message);
+        previous(info);
+    }));
+}
+
+// The code that panicked may hold a lock of the sink, so this doesn't wait for it
+fn report_panic(reporter: &Reporter, message: String) {
+    if let Err(e) = reporter.try_report(panic_record(message.clone())) {
+        eprintln!("tracing-eventlog: could not write the panic to the event log ({e}): {message}");
+    }
+}
+
+fn panic_record(message: String) -> EventRecord {
+    EventRecord {
+        event_id: eventmsgs::MSG_PANIC,
+        ..EventRecord::new(Level::ERROR, eventmsgs::PANIC_CATEGORY, message)
+    }
+}
+
+fn payload_str(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+fn panic_message(
+    payload: &str,
+    location: Option<&Location>,
+    thread_name: Option<&str>,
+    backtrace: &Backtrace,
+) -> String {
+    let mut message = format!("thread '{}' panicked", thread_name.unwrap_or("<unnamed>"));
+    if let Some(location) = location {
+        message.push_str(&format!(" at {location}"));
+    }
+    message.push_str(&format!(":\n{payload}"));
+    if backtrace.status() == BacktraceStatus::Captured {
+        message.push_str(&format!("\n\nstack backtrace:\n{backtrace}"));
+    }
+    message
+}
+
+#[cfg(test)]
+#[path = "./panic_hook_test.rs"]
+mod panic_hook_test;
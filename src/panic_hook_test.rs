@@ -0,0 +1,73 @@
+use super::*;
+use crate::{error::EventLogError, sink::MockEventSink, stats::EventLogStats};
+use std::sync::Arc;
+
+#[test]
+fn test_panic_message() {
+    let location = Location::caller();
+    let message = panic_message(
+        "something broke",
+        Some(location),
+        Some("worker"),
+        &Backtrace::disabled(),
+    );
+
+    assert_eq!(
+        format!("thread 'worker' panicked at {location}:\nsomething broke"),
+        message
+    );
+}
+
+#[test]
+fn test_panic_message_with_backtrace() {
+    let message = panic_message("something broke", None, None, &Backtrace::force_capture());
+
+    assert!(
+        message.starts_with("thread '<unnamed>' panicked:\nsomething broke\n\nstack backtrace:\n")
+    );
+}
+
+#[test]
+fn test_payload_str() {
+    assert_eq!("static", payload_str(&"static"));
+    assert_eq!("owned", payload_str(&"owned".to_owned()));
+    assert_eq!("Box<dyn Any>", payload_str(&42));
+}
+
+#[test]
+fn test_panic_record() {
+    let record = panic_record("message".to_owned());
+
+    assert_eq!(Level::ERROR, record.level);
+    assert_eq!(eventmsgs::MSG_PANIC, record.event_id);
+    assert_eq!(eventmsgs::PANIC_CATEGORY, record.category);
+}
+
+#[test]
+fn test_reports_panic_without_waiting() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().never();
+    sink.expect_try_report()
+        .withf(|record| record.event_id == eventmsgs::MSG_PANIC)
+        .times(1)
+        .returning(|_| Ok(()));
+    let stats = EventLogStats::new();
+    let reporter = Reporter::new(Arc::new(sink), stats.clone(), None);
+
+    report_panic(&reporter, "message".to_owned());
+
+    assert_eq!(1, stats.snapshot().written);
+}
+
+#[test]
+fn test_busy_sink_counts_failure() {
+    let mut sink = MockEventSink::new();
+    sink.expect_try_report()
+        .returning(|_| Err(EventLogError::Busy));
+    let stats = EventLogStats::new();
+    let reporter = Reporter::new(Arc::new(sink), stats.clone(), None);
+
+    report_panic(&reporter, "message".to_owned());
+
+    assert_eq!(Some(&1), stats.snapshot().failed.get("busy"));
+}
@@ -0,0 +1,193 @@
+use crate::{
+    error::{EventLogError, Result},
+    sink::{EventRecord, EventSink, OpenSink, try_lock},
+};
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How [`ReconnectingSink`] recovers from transient failures.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RetryPolicy {
+    /// Delay after the first failed attempt to open the event source again. Doubled after
+    /// every further failure, up to `max_backoff`.
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    /// Number of events kept for replaying once the event source is open again.
+    pub(crate) buffer_size: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(300),
+            buffer_size: 16,
+        }
+    }
+}
+
+enum State {
+    Connected(Box<dyn EventSink>),
+    Disconnected {
+        next_attempt: Instant,
+        backoff: Duration,
+    },
+}
+
+struct Inner {
+    state: State,
+    buffer: VecDeque<EventRecord>,
+}
+
+/// Opens the event source again when reporting fails with a transient error, e.g. after the
+/// EventLog service restarted or the connection to a remote computer was lost.
+///
+/// The first transient failure closes the handle and immediately opens a new one. If that fails,
+/// further attempts back off exponentially. Meanwhile events are kept in a small buffer and
+/// replayed in order once the source is open again. Buffered events are reported as
+/// [`EventLogError::Buffered`] so callers can tell them from written ones, and reports fail with
+/// [`EventLogError::Unavailable`] once the buffer is full. Permanent errors are returned without
+/// touching the handle.
+///
+/// Buffered events that can't be replayed, because of a permanent error or because the source is
+/// still unavailable when the sink is dropped, are written to stderr instead of being lost. If
+/// that happens to the event being reported, its error is returned.
+pub(crate) struct ReconnectingSink {
+    open: OpenSink,
+    policy: RetryPolicy,
+    inner: Mutex<Inner>,
+}
+
+impl ReconnectingSink {
+    pub(crate) fn new(open: OpenSink, policy: RetryPolicy) -> Result<Self> {
+        let sink = open()?;
+        Ok(Self {
+            open,
+            policy,
+            inner: Mutex::new(Inner {
+                state: State::Connected(sink),
+                buffer: VecDeque::new(),
+            }),
+        })
+    }
+
+    /// Opens the sink and replays the buffer. Returns the error the last buffered record was
+    /// discarded with, if the replay got to it and it failed permanently.
+    fn reconnect(&self, inner: &mut Inner, backoff: Duration) -> Option<EventLogError> {
+        let sink = match (self.open)() {
+            Ok(sink) => sink,
+            Err(_) => {
+                self.disconnect(inner, backoff);
+                return None;
+            }
+        };
+        let mut last_error = None;
+        while let Some(record) = inner.buffer.front() {
+            match sink.report(record) {
+                Err(e) if e.is_transient() => {
+                    self.disconnect(inner, backoff);
+                    return None;
+                }
+                // A permanent error won't go away by retrying
+                Err(e) => {
+                    if let Some(record) = inner.buffer.pop_front() {
+                        discard(&record, &e);
+                    }
+                    last_error = Some(e);
+                }
+                Ok(()) => {
+                    inner.buffer.pop_front();
+                    last_error = None;
+                }
+            }
+        }
+        inner.state = State::Connected(sink);
+        last_error
+    }
+
+    fn disconnect(&self, inner: &mut Inner, backoff: Duration) {
+        inner.state = State::Disconnected {
+            next_attempt: Instant::now() + backoff,
+            backoff: (backoff * 2).min(self.policy.max_backoff),
+        };
+    }
+
+    fn report_locked(&self, inner: &mut Inner, record: &EventRecord) -> Result<()> {
+        if let State::Connected(sink) = &inner.state {
+            match sink.report(record) {
+                Err(e) if e.is_transient() => {
+                    inner.state = State::Disconnected {
+                        next_attempt: Instant::now(),
+                        backoff: self.policy.initial_backoff,
+                    };
+                }
+                result => return result,
+            }
+        }
+
+        let buffered = inner.buffer.len() < self.policy.buffer_size;
+        if buffered {
+            inner.buffer.push_back(record.clone());
+        }
+        let mut discarded = None;
+        if let State::Disconnected {
+            next_attempt,
+            backoff,
+        } = inner.state
+        {
+            if Instant::now() >= next_attempt {
+                discarded = self.reconnect(inner, backoff);
+            }
+        }
+
+        match &inner.state {
+            // The buffer was replayed, including this record, which is last
+            State::Connected(_) if buffered => discarded.map_or(Ok(()), Err),
+            State::Connected(sink) => sink.report(record),
+            State::Disconnected { .. } if buffered => Err(EventLogError::Buffered),
+            State::Disconnected { .. } => Err(EventLogError::Unavailable),
+        }
+    }
+}
+
+impl EventSink for ReconnectingSink {
+    fn report(&self, record: &EventRecord) -> Result<()> {
+        self.report_locked(&mut self.inner.lock().unwrap(), record)
+    }
+
+    fn try_report(&self, record: &EventRecord) -> Result<()> {
+        self.report_locked(&mut *try_lock(&self.inner)?, record)
+    }
+}
+
+impl Drop for ReconnectingSink {
+    fn drop(&mut self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.buffer.is_empty() {
+            return;
+        }
+        // One last attempt, whether or not the backoff has elapsed
+        if let State::Disconnected { backoff, .. } = inner.state {
+            self.reconnect(&mut inner, backoff);
+        }
+        for record in inner.buffer.drain(..) {
+            discard(&record, &EventLogError::Unavailable);
+        }
+    }
+}
+
+/// Writes a buffered event that can't be written to the event log to stderr.
+fn discard(record: &EventRecord, cause: &EventLogError) {
+    eprintln!(
+        "tracing-eventlog: could not write a buffered event ({cause}): {:>5} {}",
+        record.level,
+        record.message.trim_end()
+    );
+}
+
+#[cfg(test)]
+#[path = "./reconnect_test.rs"]
+mod reconnect_test;
@@ -0,0 +1,310 @@
+use super::*;
+use crate::{error::RegistryError, sink::MockEventSink};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tracing::Level;
+
+fn record(message: &str) -> EventRecord {
+    EventRecord::new(Level::INFO, 0, message)
+}
+
+fn policy(initial_backoff: Duration, buffer_size: usize) -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff,
+        max_backoff: Duration::from_secs(60),
+        buffer_size,
+    }
+}
+
+fn sink(result: fn() -> Result<()>) -> Box<dyn EventSink> {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(move |_| result());
+    Box::new(sink)
+}
+
+fn recording_sink(messages: Arc<Mutex<Vec<String>>>) -> Box<dyn EventSink> {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(move |record| {
+        messages.lock().unwrap().push(record.message.clone());
+        Ok(())
+    });
+    Box::new(sink)
+}
+
+/// Records events like `recording_sink`, except those with the message "invalid", which fail
+/// permanently.
+fn rejecting_sink(messages: Arc<Mutex<Vec<String>>>) -> Box<dyn EventSink> {
+    let mut sink = MockEventSink::new();
+    sink.expect_report().returning(move |record| {
+        if record.message == "invalid" {
+            return Err(permanent_error());
+        }
+        messages.lock().unwrap().push(record.message.clone());
+        Ok(())
+    });
+    Box::new(sink)
+}
+
+fn permanent_error() -> EventLogError {
+    EventLogError::RegistryError(RegistryError::InvalidExePath)
+}
+
+/// Opens sinks from `sinks` in order, failing once they run out.
+fn open_from(sinks: Vec<Box<dyn EventSink>>, opened: Arc<AtomicUsize>) -> OpenSink {
+    let sinks = Mutex::new(sinks.into_iter());
+    Box::new(move || {
+        opened.fetch_add(1, Ordering::SeqCst);
+        sinks
+            .lock()
+            .unwrap()
+            .next()
+            .ok_or(EventLogError::Unavailable)
+    })
+}
+
+#[test]
+fn test_fails_when_first_open_fails() {
+    let opened = Arc::new(AtomicUsize::new(0));
+
+    let result = ReconnectingSink::new(open_from(vec![], opened.clone()), RetryPolicy::default());
+
+    assert!(matches!(result, Err(EventLogError::Unavailable)));
+    assert_eq!(1, opened.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_reconnects_after_transient_failure() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        recording_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(60), 16),
+    )
+    .unwrap();
+
+    sink.report(&record("first")).unwrap();
+    sink.report(&record("second")).unwrap();
+
+    assert_eq!(2, opened.load(Ordering::SeqCst));
+    assert_eq!(vec!["first", "second"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_keeps_handle_after_permanent_failure() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let sinks = vec![sink(|| Err(permanent_error()))];
+    let sink = ReconnectingSink::new(open_from(sinks, opened.clone()), policy(Duration::ZERO, 16))
+        .unwrap();
+
+    assert!(matches!(
+        sink.report(&record("event")),
+        Err(EventLogError::RegistryError(_))
+    ));
+    assert_eq!(1, opened.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_replays_buffer_in_order() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        sink(|| Err(EventLogError::Unavailable)),
+        recording_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(60), 16),
+    )
+    .unwrap();
+
+    // The first event fails on the original handle and on the one opened right away
+    assert!(matches!(
+        sink.report(&record("first")),
+        Err(EventLogError::Buffered)
+    ));
+    assert!(matches!(
+        sink.report(&record("second")),
+        Err(EventLogError::Buffered)
+    ));
+    assert_eq!(2, opened.load(Ordering::SeqCst));
+    assert!(messages.lock().unwrap().is_empty());
+
+    // Pretend the backoff has elapsed
+    let mut inner = sink.inner.lock().unwrap();
+    inner.state = State::Disconnected {
+        next_attempt: Instant::now(),
+        backoff: Duration::from_secs(60),
+    };
+    drop(inner);
+    sink.report(&record("third")).unwrap();
+
+    assert_eq!(3, opened.load(Ordering::SeqCst));
+    assert_eq!(vec!["first", "second", "third"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_fails_when_buffer_is_full() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let sinks = vec![sink(|| Err(EventLogError::Unavailable))];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(60), 1),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        sink.report(&record("buffered")),
+        Err(EventLogError::Buffered)
+    ));
+    assert!(matches!(
+        sink.report(&record("dropped")),
+        Err(EventLogError::Unavailable)
+    ));
+    assert_eq!(1, sink.inner.lock().unwrap().buffer.len());
+}
+
+#[test]
+fn test_backs_off_exponentially() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let sinks = vec![sink(|| Err(EventLogError::Unavailable))];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(20), 16),
+    )
+    .unwrap();
+    let next_backoff = || match sink.inner.lock().unwrap().state {
+        State::Disconnected { backoff, .. } => backoff,
+        State::Connected(_) => panic!("expected to be disconnected"),
+    };
+
+    // Failing to open a new handle right away waits the initial backoff before trying again
+    let _ = sink.report(&record("event"));
+    assert_eq!(Duration::from_secs(40), next_backoff());
+
+    let mut inner = sink.inner.lock().unwrap();
+    sink.reconnect(&mut inner, Duration::from_secs(40));
+    drop(inner);
+    assert_eq!(Duration::from_secs(60), next_backoff());
+    assert_eq!(3, opened.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_drains_buffer_on_drop() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        sink(|| Err(EventLogError::Unavailable)),
+        recording_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(60), 16),
+    )
+    .unwrap();
+
+    let _ = sink.report(&record("first"));
+    let _ = sink.report(&record("second"));
+    drop(sink);
+
+    assert_eq!(3, opened.load(Ordering::SeqCst));
+    assert_eq!(vec!["first", "second"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_skips_events_failing_permanently_on_replay() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        sink(|| Err(EventLogError::Unavailable)),
+        rejecting_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, opened.clone()),
+        policy(Duration::from_secs(60), 16),
+    )
+    .unwrap();
+
+    let _ = sink.report(&record("invalid"));
+    let _ = sink.report(&record("valid"));
+    drop(sink);
+
+    assert_eq!(vec!["valid"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_try_report_does_not_wait_for_lock() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![recording_sink(messages.clone())];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, Arc::new(AtomicUsize::new(0))),
+        RetryPolicy::default(),
+    )
+    .unwrap();
+
+    let inner = sink.inner.lock().unwrap();
+    assert!(matches!(
+        sink.try_report(&record("first")),
+        Err(EventLogError::Busy)
+    ));
+    drop(inner);
+
+    sink.try_report(&record("second")).unwrap();
+    assert_eq!(vec!["second"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_returns_error_of_own_event_discarded_on_replay() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        sink(|| Err(EventLogError::Unavailable)),
+        rejecting_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, Arc::new(AtomicUsize::new(0))),
+        policy(Duration::ZERO, 16),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        sink.report(&record("invalid")),
+        Err(EventLogError::Buffered)
+    ));
+    // Replays both events, discarding the buffered one and this one
+    assert!(matches!(
+        sink.report(&record("invalid")),
+        Err(EventLogError::RegistryError(_))
+    ));
+    sink.report(&record("valid")).unwrap();
+
+    assert_eq!(vec!["valid"], *messages.lock().unwrap());
+}
+
+#[test]
+fn test_discarded_earlier_event_does_not_fail_report() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sinks = vec![
+        sink(|| Err(EventLogError::Unavailable)),
+        sink(|| Err(EventLogError::Unavailable)),
+        rejecting_sink(messages.clone()),
+    ];
+    let sink = ReconnectingSink::new(
+        open_from(sinks, Arc::new(AtomicUsize::new(0))),
+        policy(Duration::ZERO, 16),
+    )
+    .unwrap();
+
+    let _ = sink.report(&record("invalid"));
+    sink.report(&record("valid")).unwrap();
+
+    assert_eq!(vec!["valid"], *messages.lock().unwrap());
+}
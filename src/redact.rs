@@ -0,0 +1,290 @@
+use crate::fields::FieldFilter;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    error::Error,
+    fmt::{self, Debug},
+    sync::Arc,
+};
+use tracing_core::field::{Field, Visit};
+use tracing_subscriber::field::{VisitFmt, VisitOutput};
+
+const MASK: &str = "***";
+
+thread_local! {
+    // Target of the event or span whose fields are being formatted
+    static TARGET: Cell<&'static str> = const { Cell::new("") };
+}
+
+/// What to do with a value matched by a [`RedactionRule`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedactAction {
+    /// Replaces the value with `***`.
+    Mask,
+    /// Replaces the value with a prefix of its SHA-256 hash, so equal values can still be
+    /// correlated.
+    Hash,
+    /// Leaves the field out entirely.
+    Drop,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Field(String),
+    Value(Regex),
+    Target(String),
+}
+
+/// Removes sensitive values from what is written to the event log.
+///
+/// Rules only apply to the event log layer's output, and are applied in the order they were
+/// added.
+#[derive(Debug)]
+pub struct RedactionRule {
+    matcher: Matcher,
+    action: RedactAction,
+}
+
+impl RedactionRule {
+    /// Matches fields by name. `*` matches any number of characters and `?` matches one.
+    pub fn field<T: Into<String>>(pattern: T, action: RedactAction) -> Self {
+        Self {
+            matcher: Matcher::Field(pattern.into()),
+            action,
+        }
+    }
+
+    /// Matches the parts of values that match `pattern`. Masking and hashing only replace the
+    /// matched text.
+    pub fn value(pattern: &str, action: RedactAction) -> Result<Self, regex::Error> {
+        Ok(Self {
+            matcher: Matcher::Value(Regex::new(pattern)?),
+            action,
+        })
+    }
+
+    /// Matches every field of events and spans whose target matches the glob `pattern`.
+    pub fn target<T: Into<String>>(pattern: T, action: RedactAction) -> Self {
+        Self {
+            matcher: Matcher::Target(pattern.into()),
+            action,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Redactions(Arc<Vec<RedactionRule>>);
+
+impl Redactions {
+    pub(crate) fn new(rules: Vec<RedactionRule>) -> Self {
+        Self(Arc::new(rules))
+    }
+
+    /// Returns the value to write for a field, or `None` if it should be dropped.
+    pub(crate) fn apply<'a>(
+        &self,
+        target: &str,
+        field: &str,
+        value: &'a str,
+    ) -> Option<Cow<'a, str>> {
+        let mut value = Cow::Borrowed(value);
+        for rule in self.0.iter() {
+            match &rule.matcher {
+                Matcher::Field(pattern) if glob_match(pattern, field) => {
+                    return replace_all(&value, rule.action);
+                }
+                Matcher::Target(pattern) if glob_match(pattern, target) => {
+                    return replace_all(&value, rule.action);
+                }
+                Matcher::Value(regex) if regex.is_match(&value) => {
+                    let replaced = match rule.action {
+                        RedactAction::Drop => return None,
+                        RedactAction::Mask => regex.replace_all(&value, MASK),
+                        RedactAction::Hash => {
+                            regex.replace_all(&value, |c: &regex::Captures| hash(&c[0]))
+                        }
+                    };
+                    value = Cow::Owned(replaced.into_owned());
+                }
+                _ => {}
+            }
+        }
+        Some(value)
+    }
+}
+
+fn replace_all<'a>(value: &str, action: RedactAction) -> Option<Cow<'a, str>> {
+    match action {
+        RedactAction::Mask => Some(Cow::Borrowed(MASK)),
+        RedactAction::Hash => Some(Cow::Owned(hash(value))),
+        RedactAction::Drop => None,
+    }
+}
+
+fn hash(value: &str) -> String {
+    let digest = Sha256::digest(value.as_bytes());
+    let hex = digest[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    format!("sha256:{hex}")
+}
+
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Runs `f` with `target` as the target used for redacting fields.
+pub(crate) fn with_target<R>(target: &'static str, f: impl FnOnce() -> R) -> R {
+    let previous = TARGET.with(|cell| cell.replace(target));
+    let result = f();
+    TARGET.with(|cell| cell.set(previous));
+    result
+}
+
+pub(crate) fn current_target() -> &'static str {
+    TARGET.with(|cell| cell.get())
+}
+
+/// Skips fields rejected by a [`FieldFilter`] and applies [`Redactions`] to the rest before
+/// passing them on to another visitor.
+pub struct RedactingVisitor<V> {
+    inner: V,
+    filter: Arc<FieldFilter>,
+    redactions: Redactions,
+    target: &'static str,
+}
+
+impl<V> RedactingVisitor<V> {
+    pub(crate) fn new(
+        inner: V,
+        filter: Arc<FieldFilter>,
+        redactions: Redactions,
+        target: &'static str,
+    ) -> Self {
+        Self {
+            inner,
+            filter,
+            redactions,
+            target,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> V {
+        self.inner
+    }
+}
+
+impl<V: Visit> RedactingVisitor<V> {
+    fn record_formatted(
+        &mut self,
+        field: &Field,
+        value: impl FnOnce() -> String,
+        record: impl FnOnce(&mut V),
+    ) {
+        if !self.filter.allows(self.target, field.name()) {
+            return;
+        }
+        let value = value();
+        match self.redactions.apply(self.target, field.name(), &value) {
+            Some(Cow::Borrowed(_)) => record(&mut self.inner),
+            Some(Cow::Owned(redacted)) => {
+                self.inner.record_debug(field, &format_args!("{redacted}"))
+            }
+            None => {}
+        }
+    }
+}
+
+impl<V: Visit> Visit for RedactingVisitor<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_f64(field, value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_i64(field, value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_u64(field, value));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_i128(field, value));
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_u128(field, value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_formatted(field, || value.to_string(), |v| v.record_bool(field, value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if !self.filter.allows(self.target, field.name()) {
+            return;
+        }
+        if let Some(value) = self.redactions.apply(self.target, field.name(), value) {
+            self.inner.record_str(field, &value);
+        }
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn Error + 'static)) {
+        self.record_formatted(
+            field,
+            || value.to_string(),
+            |v| v.record_error(field, value),
+        );
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.record_formatted(
+            field,
+            || format!("{value:?}"),
+            |v| v.record_debug(field, value),
+        );
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for RedactingVisitor<V> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for RedactingVisitor<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+#[cfg(test)]
+#[path = "./redact_test.rs"]
+mod redact_test;
@@ -0,0 +1,85 @@
+use super::*;
+
+fn redactions(rules: Vec<RedactionRule>) -> Redactions {
+    Redactions::new(rules)
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("token", "token"));
+    assert!(glob_match("*token*", "access_token_id"));
+    assert!(glob_match("user.?d", "user.id"));
+    assert!(glob_match("my_app::auth*", "my_app::auth::login"));
+    assert!(!glob_match("token", "tokens"));
+    assert!(!glob_match("*_token", "token_id"));
+}
+
+#[test]
+fn test_field_rules() {
+    let redactions = redactions(vec![
+        RedactionRule::field("*token", RedactAction::Mask),
+        RedactionRule::field("email", RedactAction::Hash),
+        RedactionRule::field("password", RedactAction::Drop),
+    ]);
+
+    assert_eq!(
+        Some("***"),
+        redactions.apply("app", "access_token", "secret").as_deref()
+    );
+    assert_eq!(
+        Some(hash("jane@example.com").as_str()),
+        redactions
+            .apply("app", "email", "jane@example.com")
+            .as_deref()
+    );
+    assert!(
+        redactions
+            .apply("app", "email", "jane@example.com")
+            .unwrap()
+            .starts_with("sha256:")
+    );
+    assert_eq!(None, redactions.apply("app", "password", "hunter2"));
+    assert_eq!(
+        Some("visible"),
+        redactions.apply("app", "user", "visible").as_deref()
+    );
+}
+
+#[test]
+fn test_value_rules() {
+    let redactions = redactions(vec![
+        RedactionRule::value(r"[\w.]+@[\w.]+", RedactAction::Mask).unwrap(),
+        RedactionRule::value(r"Bearer \S+", RedactAction::Drop).unwrap(),
+    ]);
+
+    assert_eq!(
+        Some("sent to *** and ***"),
+        redactions
+            .apply("app", "message", "sent to a@example.com and b@example.com")
+            .as_deref()
+    );
+    assert_eq!(
+        None,
+        redactions.apply("app", "header", "Authorization: Bearer abc")
+    );
+    assert!(RedactionRule::value("(", RedactAction::Mask).is_err());
+}
+
+#[test]
+fn test_target_rules() {
+    let redactions = redactions(vec![RedactionRule::target(
+        "my_app::auth*",
+        RedactAction::Mask,
+    )]);
+
+    assert_eq!(
+        Some("***"),
+        redactions
+            .apply("my_app::auth::login", "user", "jane")
+            .as_deref()
+    );
+    assert_eq!(
+        Some("jane"),
+        redactions.apply("my_app::db", "user", "jane").as_deref()
+    );
+}
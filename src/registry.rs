@@ -1,197 +1,109 @@
-use crate::error::RegistryError;
+use crate::{error::RegistryError, eventmsgs};
+
+mod auto_register;
+mod backend;
+mod enumerate;
+mod export;
+mod log_options;
+mod log_source;
+mod message_files;
+mod names;
+mod plan;
+mod report;
+mod security;
+mod status;
+pub use self::auto_register::*;
+pub use self::backend::*;
+pub use self::enumerate::{EventLogEntry, list_logs, list_sources};
+pub use self::log_options::{CustomLogOptions, Retention};
+pub use self::log_source::LogSource;
+pub use self::log_source::RemoveLog;
+pub use self::message_files::MessageFiles;
+#[cfg(feature = "serde")]
+pub(crate) use self::names::validate_new_name;
+pub use self::plan::{RegistryChange, RegistryPlan};
+pub use self::report::{DeregistrationReport, DeregistrationWarning, RegistrationReport};
+pub use self::security::{SecurityDescriptor, SecurityDescriptorBuilder};
+pub use self::status::{LogSettings, MessageFileStatus, RegistrationStatus};
+
+pub(crate) const REG_BASEKEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog";
+
+pub(crate) const APPLICATION: &str = "Application";
+
+// EVENTLOG_ERROR_TYPE | EVENTLOG_WARNING_TYPE | EVENTLOG_INFORMATION_TYPE
+pub(crate) const TYPES_SUPPORTED: u32 = 0x0001 | 0x0002 | 0x0004;
 
 pub trait EventLogRegistry {
+    /// Names are checked when registering, see [`LogSource::try_application`] to check them
+    /// right away.
     fn application(name: impl Into<String>) -> Self;
+    /// Names are checked when registering, see [`LogSource::try_custom`] to check them right
+    /// away.
     fn custom<'a>(name: impl Into<String>, sources: impl Into<Option<Vec<&'a str>>>) -> Self;
-    fn register(&self) -> core::result::Result<(), RegistryError>;
-    fn deregister(self) -> core::result::Result<(), RegistryError>;
+    /// Registers the source by executing its [`plan`](Self::plan).
+    fn register(&self) -> core::result::Result<RegistrationReport, RegistryError>;
+    fn deregister(self) -> core::result::Result<DeregistrationReport, RegistryError>;
+    /// Lists the changes [`register`](Self::register) would make, without making them.
+    fn plan(&self) -> core::result::Result<RegistryPlan, RegistryError>;
+    /// Lists the changes [`deregister`](Self::deregister) would make, without making them.
+    fn plan_deregister(&self) -> core::result::Result<RegistryPlan, RegistryError>;
+    /// Reads the current registration without changing it. For a custom log, this is the
+    /// registration of its first source.
+    fn status(&self) -> core::result::Result<RegistrationStatus, RegistryError>;
 }
 
-#[cfg(windows)]
-pub mod platform {
-    use super::EventLogRegistry;
-    use crate::{error::RegistryError, eventmsgs};
-    use windows::Win32::{
-        Foundation::ERROR_ACCESS_DENIED,
-        System::EventLog::{EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE},
-    };
-    use windows_registry::{Key, LOCAL_MACHINE, Value};
+pub(crate) fn current_exe_path() -> core::result::Result<String, RegistryError> {
+    let current_exe = std::env::current_exe().map_err(RegistryError::SystemError)?;
+    let exe_path = current_exe.to_str().ok_or(RegistryError::InvalidExePath)?;
 
-    const REG_BASEKEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog";
-
-    const APPLICATION: &str = "Application";
-
-    enum SourceType {
-        Application,
-        Custom(Vec<String>),
-    }
-
-    pub struct LogSource {
-        source: SourceType,
-        name: String,
-    }
-
-    impl LogSource {
-        fn add_keys(
-            &self,
-            app_key: Key,
-            exe_path: &str,
-        ) -> core::result::Result<(), RegistryError> {
-            set_registry_value(&app_key, "EventMessageFile", &exe_path.into())?;
-
-            set_registry_value(&app_key, "CategoryMessageFile", &exe_path.into())?;
-
-            set_registry_value(&app_key, "CategoryCount", &eventmsgs::CATEGORY_COUNT.into())?;
-
-            let supported_types =
-                EVENTLOG_ERROR_TYPE.0 | EVENTLOG_WARNING_TYPE.0 | EVENTLOG_INFORMATION_TYPE.0;
-            set_registry_value(&app_key, "TypesSupported", &(supported_types as u32).into())?;
+    Ok(exe_path.replacen("\\\\?\\", "", 1))
+}
 
-            Ok(())
+/// Expands `%VAR%` references the same way `REG_EXPAND_SZ` values are expanded.
+pub(crate) fn expand_environment(value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        let Some(len) = rest[start + 1..].find('%') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        expanded.push_str(&rest[..start]);
+        match std::env::var(name) {
+            Ok(var) if !name.is_empty() => expanded.push_str(&var),
+            _ => expanded.push_str(&rest[start..start + len + 2]),
         }
+        rest = &rest[start + len + 2..];
     }
+    expanded.push_str(rest);
+    expanded
+}
 
-    fn set_registry_value(
-        key: &Key,
-        name: &str,
-        data: &Value,
-    ) -> core::result::Result<(), RegistryError> {
-        key.set_value(name, data).map_err(|e| {
-            if e.code() == ERROR_ACCESS_DENIED.into() {
-                RegistryError::PermissionDenied(e)
-            } else {
-                RegistryError::ValueError(e)
-            }
-        })
-    }
-
-    fn map_key_error(result: ::windows::core::Error) -> RegistryError {
-        if result.code() == ERROR_ACCESS_DENIED.into() {
-            RegistryError::PermissionDenied(result)
-        } else {
-            RegistryError::KeyError(result)
-        }
-    }
+/// The keys and values registering a source writes.
+pub(crate) struct DesiredState {
+    pub(crate) log: String,
+    /// Values of the log key, only set for custom logs.
+    pub(crate) log_values: Option<Vec<(&'static str, RegistryValue)>>,
+    /// Optional values of the log key that aren't configured and are removed if present.
+    pub(crate) unset_log_values: Vec<&'static str>,
+    pub(crate) sources: Vec<String>,
+    pub(crate) source_values: Vec<(&'static str, RegistryValue)>,
+}
 
-    fn read_log_key() -> core::result::Result<Key, RegistryError> {
-        LOCAL_MACHINE
-            .options()
-            .read()
-            .open(REG_BASEKEY)
-            .map_err(map_key_error)
+impl DesiredState {
+    pub(crate) fn log_key(&self) -> String {
+        format!(r"{REG_BASEKEY}\{}", self.log)
     }
 
-    fn write_log_key() -> core::result::Result<Key, RegistryError> {
-        LOCAL_MACHINE
-            .options()
-            .read()
-            .write()
-            .create()
-            .open(REG_BASEKEY)
-            .map_err(map_key_error)
-    }
-
-    impl EventLogRegistry for LogSource {
-        fn application(name: impl Into<String>) -> Self {
-            Self {
-                source: SourceType::Application,
-                name: name.into(),
-            }
-        }
-
-        fn custom<'a>(name: impl Into<String>, sources: impl Into<Option<Vec<&'a str>>>) -> Self {
-            let name = name.into();
-            let sources = sources.into().unwrap_or_default();
-            let mut sources: Vec<String> = sources.into_iter().map(|s| s.into()).collect();
-            sources.push(name.clone());
-            Self {
-                source: SourceType::Custom(sources),
-                name,
-            }
-        }
-
-        fn register(&self) -> core::result::Result<(), RegistryError> {
-            let current_exe = std::env::current_exe().map_err(RegistryError::SystemError)?;
-            let exe_path = current_exe.to_str().ok_or(RegistryError::InvalidExePath)?;
-
-            let exe_path = &exe_path.replacen("\\\\?\\", "", 1);
-
-            match &self.source {
-                SourceType::Application => {
-                    let app_key_read = read_log_key()?.open(APPLICATION).map_err(map_key_error)?;
-                    if app_key_read.open(&self.name).is_ok() {
-                        return Ok(());
-                    }
-
-                    let app_key = write_log_key()?.open(APPLICATION).map_err(map_key_error)?;
-                    let name_key = app_key.create(&self.name).map_err(map_key_error)?;
-                    self.add_keys(name_key, exe_path)?;
-                }
-                SourceType::Custom(sources) => {
-                    let base_key_read = read_log_key()?;
-                    for source in sources {
-                        if let Ok(custom_key) = base_key_read.open(&self.name) {
-                            if custom_key.open(&self.name).is_ok()
-                                && custom_key.open(source).is_ok()
-                                && custom_key.get_value("AutoBackupLogFiles").is_ok()
-                                && custom_key.get_value("MaxSize").is_ok()
-                            {
-                                continue;
-                            }
-                        }
-
-                        let base_key = read_log_key()?;
-                        let custom_key = base_key.create(&self.name).map_err(map_key_error)?;
-                        set_registry_value(&custom_key, "AutoBackupLogFiles", &0u32.into())?;
-                        set_registry_value(&custom_key, "MaxSize", &0x00080000u32.into())?;
-                        let name_key = custom_key.create(&self.name).map_err(map_key_error)?;
-                        self.add_keys(name_key, exe_path)?;
-                        let source_key = custom_key.create(source).map_err(map_key_error)?;
-                        self.add_keys(source_key, exe_path)?;
-                    }
-                }
-            };
-
-            Ok(())
-        }
-
-        fn deregister(self) -> core::result::Result<(), RegistryError> {
-            match self.source {
-                SourceType::Application => {
-                    let app_key = write_log_key()?.open(APPLICATION).map_err(map_key_error)?;
-                    app_key.remove_tree(self.name).map_err(map_key_error)?;
-                }
-                SourceType::Custom(_) => {
-                    let base_key = write_log_key()?;
-                    base_key.remove_tree(self.name).map_err(map_key_error)?;
-                }
-            }
-            Ok(())
-        }
+    pub(crate) fn source_key(&self, source: &str) -> String {
+        format!(r"{REG_BASEKEY}\{}\{source}", self.log)
     }
 }
 
-#[cfg(not(windows))]
-pub mod platform {
-    use super::EventLogRegistry;
-    use crate::error::RegistryError;
-    pub struct LogSource;
-
-    impl EventLogRegistry for LogSource {
-        fn application(name: impl Into<String>) -> Self {
-            Self {}
-        }
-
-        fn custom<'a>(name: impl Into<String>, sources: impl Into<Option<Vec<&'a str>>>) -> Self {
-            Self {}
-        }
-
-        fn register(&self) -> core::result::Result<(), RegistryError> {
-            Ok(())
-        }
-
-        fn deregister(self) -> core::result::Result<(), RegistryError> {
-            Ok(())
-        }
-    }
+/// The values every source key should have.
+pub(crate) fn source_values(message_files: &MessageFiles) -> Vec<(&'static str, RegistryValue)> {
+    let mut values = message_files.values();
+    values.push(("CategoryCount", eventmsgs::CATEGORY_COUNT.into()));
+    values.push(("TypesSupported", TYPES_SUPPORTED.into()));
+    values
 }
@@ -0,0 +1,191 @@
+use super::{
+    APPLICATION, DesiredState, MessageFiles, REG_BASEKEY, RegistryBackend, RegistryPlan,
+    RegistryValue, expand_environment,
+    names::{check_conflicts, validate_new_name},
+    source_values,
+    status::get_u32,
+};
+use crate::{error::RegistryError, eventmsgs};
+use std::{fmt, path::Path};
+
+/// Why a source cannot be used to render events as-is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistrationProblem {
+    /// The source does not exist in any event log.
+    NotRegistered,
+    /// The source exists but has no `EventMessageFile` value.
+    MissingMessageFile { log: String },
+    /// `EventMessageFile` points at a module that does not exist.
+    MessageFileNotFound { log: String, path: String },
+    /// `CategoryCount` doesn't match the categories compiled into this crate.
+    CategoryCountMismatch {
+        log: String,
+        expected: u32,
+        actual: Option<u32>,
+    },
+    /// The source exists in a different log than the one it is registered for.
+    WrongLog { log: String, expected: String },
+}
+
+impl fmt::Display for RegistrationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRegistered => write!(f, "the source is not registered in any event log"),
+            Self::MissingMessageFile { log } => {
+                write!(f, "the source in the '{log}' log has no EventMessageFile")
+            }
+            Self::MessageFileNotFound { log, path } => write!(
+                f,
+                "the source in the '{log}' log points to a message file that does not exist: {path}"
+            ),
+            Self::CategoryCountMismatch {
+                log,
+                expected,
+                actual: Some(actual),
+            } => write!(
+                f,
+                "the source in the '{log}' log has CategoryCount {actual} instead of {expected}"
+            ),
+            Self::CategoryCountMismatch { log, .. } => {
+                write!(f, "the source in the '{log}' log has no CategoryCount")
+            }
+            Self::WrongLog { log, expected } => write!(
+                f,
+                "the source is registered in the '{log}' log instead of '{expected}'"
+            ),
+        }
+    }
+}
+
+/// The result of [`ensure_registered`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SourceRegistration {
+    /// The source was already registered with a valid message file.
+    Valid { log: String },
+    /// The source had a problem and was registered for the given message file.
+    Registered {
+        log: String,
+        problem: RegistrationProblem,
+    },
+}
+
+/// Returns the name of the log that contains `source`, if any.
+pub fn find_source_log(
+    backend: &impl RegistryBackend,
+    source: &str,
+) -> Result<Option<String>, RegistryError> {
+    if !backend.key_exists(REG_BASEKEY)? {
+        return Ok(None);
+    }
+    for log in backend.subkeys(REG_BASEKEY)? {
+        if backend.key_exists(&format!(r"{REG_BASEKEY}\{log}\{source}"))? {
+            return Ok(Some(log));
+        }
+    }
+    Ok(None)
+}
+
+/// Checks that `source` is registered with a message file that exists and with the categories
+/// compiled into this crate.
+///
+/// Returns the log containing the source. Pass `false` for `check_files` when `backend` is the
+/// registry of a remote computer, whose paths can't be checked from this machine.
+pub fn check_registration(
+    backend: &impl RegistryBackend,
+    source: &str,
+    check_files: bool,
+) -> Result<core::result::Result<String, RegistrationProblem>, RegistryError> {
+    let Some(log) = find_source_log(backend, source)? else {
+        return Ok(Err(RegistrationProblem::NotRegistered));
+    };
+    let files = match backend.get_value(
+        &format!(r"{REG_BASEKEY}\{log}\{source}"),
+        "EventMessageFile",
+    )? {
+        Some(RegistryValue::String(files) | RegistryValue::ExpandString(files)) => files,
+        _ => return Ok(Err(RegistrationProblem::MissingMessageFile { log })),
+    };
+
+    // The value may hold several modules separated by semicolons
+    let paths = files.split(';').map(str::trim).filter(|p| !p.is_empty());
+    for path in paths.filter(|_| check_files) {
+        if !Path::new(&expand_environment(path)).is_file() {
+            return Ok(Err(RegistrationProblem::MessageFileNotFound {
+                log,
+                path: path.to_owned(),
+            }));
+        }
+    }
+
+    let category_count = get_u32(
+        backend,
+        &format!(r"{REG_BASEKEY}\{log}\{source}"),
+        "CategoryCount",
+    )?;
+    if category_count != Some(eventmsgs::CATEGORY_COUNT) {
+        return Ok(Err(RegistrationProblem::CategoryCountMismatch {
+            log,
+            expected: eventmsgs::CATEGORY_COUNT,
+            actual: category_count,
+        }));
+    }
+    Ok(Ok(log))
+}
+
+/// Makes sure `source` can render events, registering it with `message_files` if needed.
+///
+/// Missing sources are added to the Application log, unless their name clashes with a log or with
+/// a source of another log. Sources with a stale message file are updated in place. Writing to
+/// the registry requires administrator rights; if it fails, [`RegistryError::RegistrationFailed`]
+/// explains what was wrong with the registration. `check_files` is passed on to
+/// [`check_registration`].
+///
+/// The changes are made with [`RegistryPlan::execute`], so a failure leaves the registration as
+/// it was.
+pub fn ensure_registered(
+    backend: &impl RegistryBackend,
+    source: &str,
+    message_files: &MessageFiles,
+    check_files: bool,
+) -> Result<SourceRegistration, RegistryError> {
+    validate_new_name(source)?;
+    let problem = match check_registration(backend, source, check_files)? {
+        Ok(log) => return Ok(SourceRegistration::Valid { log }),
+        Err(problem) => problem,
+    };
+    let log = match &problem {
+        RegistrationProblem::NotRegistered => APPLICATION.to_owned(),
+        RegistrationProblem::MissingMessageFile { log }
+        | RegistrationProblem::MessageFileNotFound { log, .. }
+        | RegistrationProblem::CategoryCountMismatch { log, .. }
+        | RegistrationProblem::WrongLog { log, .. } => log.clone(),
+    };
+
+    let state = DesiredState {
+        log: log.clone(),
+        log_values: None,
+        unset_log_values: Vec::new(),
+        sources: vec![source.to_owned()],
+        source_values: source_values(message_files),
+    };
+    check_conflicts(backend, &state, false)?;
+
+    let source_key = state.source_key(source);
+    let mut plan = RegistryPlan::default();
+    plan.create_key_if_missing(backend, &source_key)?;
+    for (name, value) in &state.source_values {
+        plan.set_value_if_changed(backend, &source_key, name, value.clone())?;
+    }
+    plan.execute(backend)
+        .map_err(|e| RegistryError::RegistrationFailed {
+            name: source.to_owned(),
+            problem: problem.clone(),
+            cause: Box::new(e),
+        })?;
+
+    Ok(SourceRegistration::Registered { log, problem })
+}
+
+#[cfg(test)]
+#[path = "./auto_register_test.rs"]
+mod auto_register_test;
@@ -0,0 +1,285 @@
+use super::*;
+use crate::{InMemoryRegistry, eventmsgs};
+
+const SOURCE_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\Application\Test Source";
+
+// Denies writing values, so registering fails after the source key was created
+struct ReadOnlyValuesRegistry(InMemoryRegistry);
+
+impl RegistryBackend for ReadOnlyValuesRegistry {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+        self.0.key_exists(path)
+    }
+
+    fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+        self.0.create_key(path)
+    }
+
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+        self.0.remove_tree(path)
+    }
+
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.0.subkeys(path)
+    }
+
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError> {
+        self.0.get_value(path, name)
+    }
+
+    fn set_value(
+        &self,
+        _path: &str,
+        _name: &str,
+        _value: &RegistryValue,
+    ) -> Result<(), RegistryError> {
+        Err(access_denied())
+    }
+
+    fn delete_value(&self, _path: &str, _name: &str) -> Result<(), RegistryError> {
+        Err(access_denied())
+    }
+
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.0.value_names(path)
+    }
+}
+
+#[cfg(windows)]
+fn access_denied() -> RegistryError {
+    RegistryError::PermissionDenied(
+        windows::Win32::Foundation::ERROR_ACCESS_DENIED
+            .to_hresult()
+            .into(),
+    )
+}
+
+#[cfg(not(windows))]
+fn access_denied() -> RegistryError {
+    RegistryError::PermissionDenied("access denied".to_owned())
+}
+
+fn exe_path() -> String {
+    std::env::current_exe()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn exe_files() -> MessageFiles {
+    MessageFiles::new().event_message_file(exe_path())
+}
+
+fn set_category_count(registry: &InMemoryRegistry, key: &str) {
+    registry
+        .set_value(key, "CategoryCount", &eventmsgs::CATEGORY_COUNT.into())
+        .unwrap();
+}
+
+#[test]
+fn test_registers_missing_source() {
+    let registry = InMemoryRegistry::new();
+    let exe_path = exe_path();
+
+    let result = ensure_registered(&registry, "Test Source", &exe_files(), true).unwrap();
+
+    assert_eq!(
+        SourceRegistration::Registered {
+            log: "Application".to_owned(),
+            problem: RegistrationProblem::NotRegistered
+        },
+        result
+    );
+    assert_eq!(
+        Some(RegistryValue::String(exe_path)),
+        registry.get_value(SOURCE_KEY, "EventMessageFile").unwrap()
+    );
+    assert_eq!(
+        Some(RegistryValue::U32(7)),
+        registry.get_value(SOURCE_KEY, "TypesSupported").unwrap()
+    );
+    assert_eq!(
+        Ok(String::from("Application")),
+        check_registration(&registry, "Test Source", true).unwrap()
+    );
+}
+
+#[test]
+fn test_valid_registration_is_untouched() {
+    let registry = InMemoryRegistry::new();
+    let key = r"SYSTEM\CurrentControlSet\Services\EventLog\Custom\Test Source";
+    registry.create_key(key).unwrap();
+    registry
+        .set_value(key, "EventMessageFile", &RegistryValue::String(exe_path()))
+        .unwrap();
+    set_category_count(&registry, key);
+
+    let other_files = MessageFiles::new().event_message_file("other.exe");
+    let result = ensure_registered(&registry, "test source", &other_files, true).unwrap();
+
+    assert_eq!(
+        SourceRegistration::Valid {
+            log: "Custom".to_owned()
+        },
+        result
+    );
+    assert_eq!(2, registry.values(key).len());
+}
+
+#[test]
+fn test_repairs_stale_message_file() {
+    let registry = InMemoryRegistry::new();
+    let stale = format!("{};missing-module.dll", exe_path());
+    registry.create_key(SOURCE_KEY).unwrap();
+    registry
+        .set_value(
+            SOURCE_KEY,
+            "EventMessageFile",
+            &RegistryValue::String(stale),
+        )
+        .unwrap();
+
+    let result = ensure_registered(&registry, "Test Source", &exe_files(), true).unwrap();
+
+    assert_eq!(
+        SourceRegistration::Registered {
+            log: "Application".to_owned(),
+            problem: RegistrationProblem::MessageFileNotFound {
+                log: "Application".to_owned(),
+                path: "missing-module.dll".to_owned()
+            }
+        },
+        result
+    );
+    assert_eq!(
+        Some(RegistryValue::String(exe_path())),
+        registry.get_value(SOURCE_KEY, "EventMessageFile").unwrap()
+    );
+}
+
+#[test]
+fn test_remote_message_files_are_not_checked() {
+    let registry = InMemoryRegistry::new();
+    registry.create_key(SOURCE_KEY).unwrap();
+    registry
+        .set_value(
+            SOURCE_KEY,
+            "EventMessageFile",
+            &RegistryValue::String(r"D:\Apps\only-on-server.dll".to_owned()),
+        )
+        .unwrap();
+    set_category_count(&registry, SOURCE_KEY);
+
+    let result = ensure_registered(&registry, "Test Source", &exe_files(), false).unwrap();
+
+    assert_eq!(
+        SourceRegistration::Valid {
+            log: "Application".to_owned()
+        },
+        result
+    );
+}
+
+#[test]
+fn test_repairs_stale_category_count() {
+    let registry = InMemoryRegistry::new();
+    registry.create_key(SOURCE_KEY).unwrap();
+    registry
+        .set_value(
+            SOURCE_KEY,
+            "EventMessageFile",
+            &RegistryValue::String(exe_path()),
+        )
+        .unwrap();
+    registry
+        .set_value(SOURCE_KEY, "CategoryCount", &RegistryValue::U32(3))
+        .unwrap();
+
+    let result = ensure_registered(&registry, "Test Source", &exe_files(), true).unwrap();
+
+    assert_eq!(
+        SourceRegistration::Registered {
+            log: "Application".to_owned(),
+            problem: RegistrationProblem::CategoryCountMismatch {
+                log: "Application".to_owned(),
+                expected: eventmsgs::CATEGORY_COUNT,
+                actual: Some(3)
+            }
+        },
+        result
+    );
+    assert_eq!(
+        Some(RegistryValue::U32(eventmsgs::CATEGORY_COUNT)),
+        registry.get_value(SOURCE_KEY, "CategoryCount").unwrap()
+    );
+}
+
+#[test]
+fn test_failed_registration_is_rolled_back() {
+    let registry = ReadOnlyValuesRegistry(InMemoryRegistry::new());
+
+    let err = ensure_registered(&registry, "Test Source", &exe_files(), true).unwrap_err();
+
+    assert!(
+        matches!(err, RegistryError::RegistrationFailed { .. }),
+        "{err}"
+    );
+    assert!(!registry.0.key_exists(SOURCE_KEY).unwrap());
+}
+
+#[test]
+fn test_missing_message_file_value() {
+    let registry = InMemoryRegistry::new();
+    registry.create_key(SOURCE_KEY).unwrap();
+
+    assert_eq!(
+        Err(RegistrationProblem::MissingMessageFile {
+            log: "Application".to_owned()
+        }),
+        check_registration(&registry, "Test Source", true).unwrap()
+    );
+}
+
+#[test]
+fn test_reports_failed_registration() {
+    let registry = ReadOnlyValuesRegistry(InMemoryRegistry::new());
+    registry.0.create_key(SOURCE_KEY).unwrap();
+
+    let err = ensure_registered(&registry, "Test Source", &exe_files(), true).unwrap_err();
+
+    match &err {
+        RegistryError::RegistrationFailed {
+            name,
+            problem,
+            cause,
+        } => {
+            assert_eq!("Test Source", name);
+            assert_eq!(
+                &RegistrationProblem::MissingMessageFile {
+                    log: "Application".to_owned()
+                },
+                problem
+            );
+            assert!(matches!(**cause, RegistryError::PermissionDenied(_)));
+        }
+        _ => panic!("unexpected error {err:?}"),
+    }
+    assert!(err.to_string().contains("has no EventMessageFile"));
+}
+
+#[test]
+fn test_source_named_like_log_is_not_registered() {
+    let registry = InMemoryRegistry::new();
+    registry
+        .create_key(&format!(r"{REG_BASEKEY}\Audit\Web"))
+        .unwrap();
+
+    let err = ensure_registered(&registry, "audit", &exe_files(), true).unwrap_err();
+
+    assert!(matches!(err, RegistryError::ReservedName(_)), "{err}");
+    assert!(
+        !registry
+            .key_exists(&format!(r"{REG_BASEKEY}\{APPLICATION}\audit"))
+            .unwrap()
+    );
+}
@@ -0,0 +1,589 @@
+use crate::error::RegistryError;
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// A registry value as stored by the event log service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
+pub enum RegistryValue {
+    String(String),
+    ExpandString(String),
+    U32(u32),
+    MultiString(Vec<String>),
+    Binary(Vec<u8>),
+}
+
+impl From<&str> for RegistryValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for RegistryValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<u32> for RegistryValue {
+    fn from(value: u32) -> Self {
+        Self::U32(value)
+    }
+}
+
+/// Storage used to read and write event log registrations.
+///
+/// All paths are relative to `HKEY_LOCAL_MACHINE` and use `\` as the separator. Key and value
+/// names are case-insensitive, matching the Windows registry.
+pub trait RegistryBackend {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError>;
+    fn create_key(&self, path: &str) -> Result<(), RegistryError>;
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError>;
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError>;
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError>;
+    fn set_value(&self, path: &str, name: &str, value: &RegistryValue)
+    -> Result<(), RegistryError>;
+    /// Removes a value. Succeeds if the key exists but the value doesn't.
+    fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError>;
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError>;
+
+    /// Number of records in `log`, or `None` if it can't be determined.
+    fn record_count(&self, _log: &str) -> Result<Option<u32>, RegistryError> {
+        Ok(None)
+    }
+
+    /// Starts a transaction, or returns `None` if the backend doesn't support them.
+    ///
+    /// Changes made through the transaction only become visible once it is committed, and are
+    /// discarded if it is dropped without committing.
+    fn transaction(&self) -> Result<Option<Box<dyn RegistryTransaction + '_>>, RegistryError> {
+        Ok(None)
+    }
+}
+
+/// A set of registry changes that are applied all at once or not at all.
+pub trait RegistryTransaction: RegistryBackend {
+    fn commit(self: Box<Self>) -> Result<(), RegistryError>;
+}
+
+impl<B: RegistryBackend + ?Sized> RegistryBackend for &B {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+        (**self).key_exists(path)
+    }
+
+    fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+        (**self).create_key(path)
+    }
+
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+        (**self).remove_tree(path)
+    }
+
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        (**self).subkeys(path)
+    }
+
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError> {
+        (**self).get_value(path, name)
+    }
+
+    fn set_value(
+        &self,
+        path: &str,
+        name: &str,
+        value: &RegistryValue,
+    ) -> Result<(), RegistryError> {
+        (**self).set_value(path, name, value)
+    }
+
+    fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+        (**self).delete_value(path, name)
+    }
+
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        (**self).value_names(path)
+    }
+
+    fn record_count(&self, log: &str) -> Result<Option<u32>, RegistryError> {
+        (**self).record_count(log)
+    }
+
+    fn transaction(&self) -> Result<Option<Box<dyn RegistryTransaction + '_>>, RegistryError> {
+        (**self).transaction()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MemoryKey {
+    path: String,
+    values: BTreeMap<String, (String, RegistryValue)>,
+}
+
+/// A registry held entirely in memory.
+///
+/// Useful for exercising registration logic on machines without a Windows registry.
+#[derive(Debug, Default)]
+pub struct InMemoryRegistry {
+    keys: Mutex<BTreeMap<String, MemoryKey>>,
+    record_counts: Mutex<BTreeMap<String, u32>>,
+}
+
+impl InMemoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the paths of every key in the registry, in their original casing.
+    pub fn keys(&self) -> Vec<String> {
+        self.keys
+            .lock()
+            .unwrap()
+            .values()
+            .map(|key| key.path.clone())
+            .collect()
+    }
+
+    /// Returns every value stored directly under `path`.
+    pub fn values(&self, path: &str) -> Vec<(String, RegistryValue)> {
+        self.keys
+            .lock()
+            .unwrap()
+            .get(&normalize(path))
+            .map(|key| key.values.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Sets the number of records reported for `log`.
+    pub fn set_record_count(&self, log: &str, count: u32) {
+        self.record_counts
+            .lock()
+            .unwrap()
+            .insert(log.to_lowercase(), count);
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_matches('\\').to_lowercase()
+}
+
+impl RegistryBackend for InMemoryRegistry {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+        Ok(self.keys.lock().unwrap().contains_key(&normalize(path)))
+    }
+
+    fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+        let mut keys = self.keys.lock().unwrap();
+        let path = path.trim_matches('\\');
+        let mut end = 0;
+        // Create every missing ancestor like RegCreateKeyEx does
+        for part in path.split('\\') {
+            end += part.len();
+            let key_path = &path[..end];
+            keys.entry(normalize(key_path))
+                .or_insert_with(|| MemoryKey {
+                    path: key_path.to_owned(),
+                    values: BTreeMap::new(),
+                });
+            end += 1;
+        }
+        Ok(())
+    }
+
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+        let mut keys = self.keys.lock().unwrap();
+        let path = normalize(path);
+        if !keys.contains_key(&path) {
+            return Err(RegistryError::KeyNotFound(path));
+        }
+        let prefix = format!("{path}\\");
+        keys.retain(|key, _| key != &path && !key.starts_with(&prefix));
+        Ok(())
+    }
+
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        let keys = self.keys.lock().unwrap();
+        let path = normalize(path);
+        if !keys.contains_key(&path) {
+            return Err(RegistryError::KeyNotFound(path));
+        }
+        let prefix = format!("{path}\\");
+        Ok(keys
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix) && !key[prefix.len()..].contains('\\'))
+            .map(|(_, key)| key.path[prefix.len()..].to_owned())
+            .collect())
+    }
+
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .get(&normalize(path))
+            .and_then(|key| key.values.get(&name.to_lowercase()))
+            .map(|(_, value)| value.clone()))
+    }
+
+    fn set_value(
+        &self,
+        path: &str,
+        name: &str,
+        value: &RegistryValue,
+    ) -> Result<(), RegistryError> {
+        let mut keys = self.keys.lock().unwrap();
+        let path = normalize(path);
+        let key = keys
+            .get_mut(&path)
+            .ok_or(RegistryError::KeyNotFound(path))?;
+        key.values
+            .insert(name.to_lowercase(), (name.to_owned(), value.clone()));
+        Ok(())
+    }
+
+    fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+        let mut keys = self.keys.lock().unwrap();
+        let path = normalize(path);
+        let key = keys
+            .get_mut(&path)
+            .ok_or(RegistryError::KeyNotFound(path))?;
+        key.values.remove(&name.to_lowercase());
+        Ok(())
+    }
+
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        let keys = self.keys.lock().unwrap();
+        let path = normalize(path);
+        let key = keys.get(&path).ok_or(RegistryError::KeyNotFound(path))?;
+        Ok(key.values.values().map(|(name, _)| name.clone()).collect())
+    }
+
+    fn record_count(&self, log: &str) -> Result<Option<u32>, RegistryError> {
+        Ok(self
+            .record_counts
+            .lock()
+            .unwrap()
+            .get(&log.to_lowercase())
+            .copied())
+    }
+}
+
+#[cfg(windows)]
+pub use self::platform::*;
+
+#[cfg(windows)]
+mod platform {
+    use super::{RegistryBackend, RegistryTransaction, RegistryValue};
+    use crate::error::RegistryError;
+    use windows::{
+        Win32::{
+            Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND},
+            System::{
+                EventLog::{CloseEventLog, GetNumberOfEventLogRecords, OpenEventLogW},
+                Registry::{HKEY, HKEY_LOCAL_MACHINE, RegConnectRegistryW},
+            },
+        },
+        core::{HSTRING, PCWSTR},
+    };
+    use windows_registry::{Key, LOCAL_MACHINE, Transaction, Type};
+
+    /// The registry of the local machine, or of a remote computer.
+    #[derive(Clone, Debug, Default)]
+    pub struct WindowsRegistry {
+        server: Option<String>,
+    }
+
+    /// Changes to the local registry made in a kernel transaction, started by
+    /// [`WindowsRegistry::transaction`](RegistryBackend::transaction).
+    struct TransactedRegistry {
+        transaction: Transaction,
+    }
+
+    /// The keys of a registry, as seen from inside a transaction if there is one.
+    struct Session<'a> {
+        server: Option<&'a str>,
+        transaction: Option<&'a Transaction>,
+    }
+
+    impl WindowsRegistry {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Connects to the registry of `server`, given as a computer name or `\\name`.
+        pub fn remote<T: Into<String>>(server: T) -> Self {
+            Self {
+                server: Some(server.into()),
+            }
+        }
+
+        fn session(&self) -> Session<'_> {
+            Session {
+                server: self.server.as_deref(),
+                transaction: None,
+            }
+        }
+    }
+
+    impl TransactedRegistry {
+        fn session(&self) -> Session<'_> {
+            Session {
+                server: None,
+                transaction: Some(&self.transaction),
+            }
+        }
+    }
+
+    impl Session<'_> {
+        fn open(&self, path: &str, write: bool, create: bool) -> ::windows::core::Result<Key> {
+            let root = connect(self.server)?;
+            let mut options = root.key().options();
+            options.read();
+            if write {
+                options.write();
+            }
+            if create {
+                options.create();
+            }
+            if let Some(transaction) = self.transaction {
+                options.transaction(transaction);
+            }
+            options.open(path)
+        }
+
+        fn open_read(&self, path: &str) -> Result<Option<Key>, RegistryError> {
+            match self.open(path, false, false) {
+                Ok(key) => Ok(Some(key)),
+                Err(e) if is_not_found(&e) => Ok(None),
+                Err(e) => Err(map_key_error(e)),
+            }
+        }
+
+        fn open_write(&self, path: &str) -> Result<Key, RegistryError> {
+            self.open(path, true, false).map_err(map_key_error)
+        }
+    }
+
+    /// `HKEY_LOCAL_MACHINE` of the local machine or a remote computer.
+    enum RootKey {
+        Local,
+        Remote(Key),
+    }
+
+    impl RootKey {
+        fn key(&self) -> &Key {
+            match self {
+                RootKey::Local => LOCAL_MACHINE,
+                RootKey::Remote(key) => key,
+            }
+        }
+    }
+
+    fn connect(server: Option<&str>) -> ::windows::core::Result<RootKey> {
+        let Some(server) = server else {
+            return Ok(RootKey::Local);
+        };
+        let mut handle = HKEY::default();
+        unsafe { RegConnectRegistryW(&HSTRING::from(server), HKEY_LOCAL_MACHINE, &mut handle) }
+            .ok()?;
+        Ok(RootKey::Remote(unsafe { Key::from_raw(handle.0) }))
+    }
+
+    fn map_key_error(result: ::windows::core::Error) -> RegistryError {
+        if result.code() == ERROR_ACCESS_DENIED.into() {
+            RegistryError::PermissionDenied(result)
+        } else {
+            RegistryError::KeyError(result)
+        }
+    }
+
+    fn map_value_error(result: ::windows::core::Error) -> RegistryError {
+        if result.code() == ERROR_ACCESS_DENIED.into() {
+            RegistryError::PermissionDenied(result)
+        } else {
+            RegistryError::ValueError(result)
+        }
+    }
+
+    fn is_not_found(result: &::windows::core::Error) -> bool {
+        result.code() == ERROR_FILE_NOT_FOUND.into()
+    }
+
+    impl RegistryBackend for Session<'_> {
+        fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+            Ok(self.open_read(path)?.is_some())
+        }
+
+        fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+            self.open(path, true, true).map_err(map_key_error)?;
+            Ok(())
+        }
+
+        fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+            let (parent, name) = path
+                .rsplit_once('\\')
+                .ok_or_else(|| RegistryError::KeyNotFound(path.to_owned()))?;
+            self.open_write(parent)?
+                .remove_tree(name)
+                .map_err(map_key_error)
+        }
+
+        fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+            let key = self
+                .open_read(path)?
+                .ok_or_else(|| RegistryError::KeyNotFound(path.to_owned()))?;
+            Ok(key.keys().map_err(map_key_error)?.collect())
+        }
+
+        fn get_value(
+            &self,
+            path: &str,
+            name: &str,
+        ) -> Result<Option<RegistryValue>, RegistryError> {
+            let Some(key) = self.open_read(path)? else {
+                return Ok(None);
+            };
+            let ty = match key.get_type(name) {
+                Ok(ty) => ty,
+                Err(e) if is_not_found(&e) => return Ok(None),
+                Err(e) => return Err(map_value_error(e)),
+            };
+            let value = match ty {
+                Type::U32 => RegistryValue::U32(key.get_u32(name).map_err(map_value_error)?),
+                Type::String => {
+                    RegistryValue::String(key.get_string(name).map_err(map_value_error)?)
+                }
+                Type::ExpandString => {
+                    RegistryValue::ExpandString(key.get_string(name).map_err(map_value_error)?)
+                }
+                Type::MultiString => {
+                    RegistryValue::MultiString(key.get_multi_string(name).map_err(map_value_error)?)
+                }
+                _ => RegistryValue::Binary(key.get_value(name).map_err(map_value_error)?.to_vec()),
+            };
+            Ok(Some(value))
+        }
+
+        fn set_value(
+            &self,
+            path: &str,
+            name: &str,
+            value: &RegistryValue,
+        ) -> Result<(), RegistryError> {
+            let key = self.open_write(path)?;
+            match value {
+                RegistryValue::String(value) => key.set_string(name, value),
+                RegistryValue::ExpandString(value) => key.set_expand_string(name, value),
+                RegistryValue::U32(value) => key.set_u32(name, *value),
+                RegistryValue::MultiString(value) => {
+                    let value: Vec<&str> = value.iter().map(|v| v.as_str()).collect();
+                    key.set_multi_string(name, &value)
+                }
+                RegistryValue::Binary(value) => key.set_bytes(name, Type::Bytes, value),
+            }
+            .map_err(map_value_error)
+        }
+
+        fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+            match self.open_write(path)?.remove_value(name) {
+                Err(e) if !is_not_found(&e) => Err(map_value_error(e)),
+                _ => Ok(()),
+            }
+        }
+
+        fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+            let key = self
+                .open_read(path)?
+                .ok_or_else(|| RegistryError::KeyNotFound(path.to_owned()))?;
+            Ok(key
+                .values()
+                .map_err(map_value_error)?
+                .map(|(name, _)| name)
+                .collect())
+        }
+    }
+
+    /// Implements [`RegistryBackend`] by forwarding to a [`Session`].
+    macro_rules! forward_to_session {
+        () => {
+            fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+                self.session().key_exists(path)
+            }
+
+            fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+                self.session().create_key(path)
+            }
+
+            fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+                self.session().remove_tree(path)
+            }
+
+            fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+                self.session().subkeys(path)
+            }
+
+            fn get_value(
+                &self,
+                path: &str,
+                name: &str,
+            ) -> Result<Option<RegistryValue>, RegistryError> {
+                self.session().get_value(path, name)
+            }
+
+            fn set_value(
+                &self,
+                path: &str,
+                name: &str,
+                value: &RegistryValue,
+            ) -> Result<(), RegistryError> {
+                self.session().set_value(path, name, value)
+            }
+
+            fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+                self.session().delete_value(path, name)
+            }
+
+            fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+                self.session().value_names(path)
+            }
+        };
+    }
+
+    impl RegistryBackend for WindowsRegistry {
+        forward_to_session!();
+
+        fn record_count(&self, log: &str) -> Result<Option<u32>, RegistryError> {
+            let server = self.server.as_deref().map(HSTRING::from);
+            let server = server
+                .as_ref()
+                .map_or(PCWSTR::null(), |server| PCWSTR::from_raw(server.as_ptr()));
+            let Ok(handle) = (unsafe { OpenEventLogW(server, &HSTRING::from(log)) }) else {
+                return Ok(None);
+            };
+            let mut count = 0;
+            let result = unsafe { GetNumberOfEventLogRecords(handle, &mut count) };
+            let _ = unsafe { CloseEventLog(handle) };
+            Ok(result.ok().map(|_| count))
+        }
+
+        fn transaction(&self) -> Result<Option<Box<dyn RegistryTransaction + '_>>, RegistryError> {
+            // Transactions are only available on the local machine
+            if self.server.is_some() {
+                return Ok(None);
+            }
+            let Ok(transaction) = Transaction::new() else {
+                return Ok(None);
+            };
+            Ok(Some(Box::new(TransactedRegistry { transaction })))
+        }
+    }
+
+    impl RegistryBackend for TransactedRegistry {
+        forward_to_session!();
+    }
+
+    impl RegistryTransaction for TransactedRegistry {
+        fn commit(self: Box<Self>) -> Result<(), RegistryError> {
+            self.transaction.commit().map_err(map_key_error)
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use super::{REG_BASEKEY, RegistryBackend};
+use crate::error::RegistryError;
+
+/// An event log and the sources registered in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventLogEntry {
+    pub name: String,
+    pub sources: Vec<String>,
+}
+
+/// Lists every event log with its sources.
+///
+/// Returns nothing if the event log service has no registry key at all.
+pub fn list_logs(backend: &impl RegistryBackend) -> Result<Vec<EventLogEntry>, RegistryError> {
+    if !backend.key_exists(REG_BASEKEY)? {
+        return Ok(Vec::new());
+    }
+    backend
+        .subkeys(REG_BASEKEY)?
+        .into_iter()
+        .map(|name| {
+            let sources = list_sources(backend, &name)?;
+            Ok(EventLogEntry { name, sources })
+        })
+        .collect()
+}
+
+/// Lists the sources registered in `log`.
+pub fn list_sources(
+    backend: &impl RegistryBackend,
+    log: &str,
+) -> Result<Vec<String>, RegistryError> {
+    backend.subkeys(&format!(r"{REG_BASEKEY}\{log}"))
+}
+
+#[cfg(test)]
+#[path = "./enumerate_test.rs"]
+mod enumerate_test;
@@ -0,0 +1,43 @@
+use super::*;
+use crate::{EventLogRegistry, InMemoryRegistry, LogSource, find_source_log};
+
+#[test]
+fn test_empty_registry() {
+    assert!(list_logs(&InMemoryRegistry::new()).unwrap().is_empty());
+}
+
+#[test]
+fn test_lists_logs_and_sources() {
+    let registry = InMemoryRegistry::new();
+    LogSource::application("Test App")
+        .register_with(&registry)
+        .unwrap();
+    LogSource::custom("Test Log", vec!["Extra Source"])
+        .register_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            EventLogEntry {
+                name: "Application".to_owned(),
+                sources: vec!["Test App".to_owned()],
+            },
+            EventLogEntry {
+                name: "Test Log".to_owned(),
+                sources: vec!["Extra Source".to_owned(), "Test Log".to_owned()],
+            },
+        ],
+        list_logs(&registry).unwrap()
+    );
+    assert_eq!(
+        Some("Test Log".to_owned()),
+        find_source_log(&registry, "Extra Source").unwrap()
+    );
+}
+
+#[test]
+fn test_missing_log() {
+    let err = list_sources(&InMemoryRegistry::new(), "Missing").unwrap_err();
+
+    assert!(matches!(err, RegistryError::KeyNotFound(_)));
+}
@@ -0,0 +1,215 @@
+use super::{DesiredState, RegistryValue};
+use std::fmt::Write;
+
+const REG_HEADER: &str = "Windows Registry Editor Version 5.00";
+
+pub(crate) fn reg_file(state: &DesiredState) -> String {
+    let mut out = format!("{REG_HEADER}\r\n");
+    let mut write_key = |path: &str, values: &[(&str, RegistryValue)]| {
+        write!(out, "\r\n[HKEY_LOCAL_MACHINE\\{path}]\r\n").unwrap();
+        for (name, value) in values {
+            write!(out, "\"{}\"={}\r\n", reg_escape(name), reg_value(value)).unwrap();
+        }
+    };
+    if let Some(log_values) = &state.log_values {
+        write_key(&state.log_key(), log_values);
+    }
+    for source in &state.sources {
+        write_key(&state.source_key(source), &state.source_values);
+    }
+    out
+}
+
+fn reg_escape(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"")
+}
+
+fn reg_value(value: &RegistryValue) -> String {
+    match value {
+        RegistryValue::String(value) => format!("\"{}\"", reg_escape(value)),
+        RegistryValue::U32(value) => format!("dword:{value:08x}"),
+        RegistryValue::ExpandString(value) => format!("hex(2):{}", utf16_hex([value.as_str()])),
+        RegistryValue::MultiString(values) => {
+            // Each string is terminated, followed by an empty string ending the list
+            let strings = values.iter().map(String::as_str).chain([""]);
+            format!("hex(7):{}", utf16_hex(strings))
+        }
+        RegistryValue::Binary(bytes) => format!("hex:{}", hex_bytes(bytes.iter().copied())),
+    }
+}
+
+fn utf16_hex<'a>(strings: impl IntoIterator<Item = &'a str>) -> String {
+    let bytes = strings
+        .into_iter()
+        .flat_map(|s| s.encode_utf16().chain([0]))
+        .flat_map(u16::to_le_bytes);
+    hex_bytes(bytes)
+}
+
+fn hex_bytes(bytes: impl Iterator<Item = u8>) -> String {
+    bytes
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn powershell(state: &DesiredState) -> String {
+    let mut out =
+        String::from("#Requires -RunAsAdministrator\n$ErrorActionPreference = 'Stop'\n\n");
+    // A source registered in another log would stay there, so that is an error
+    let log = ps_quote(&state.log);
+    for source in &state.sources {
+        let source = ps_quote(source);
+        writeln!(
+            out,
+            "if ([System.Diagnostics.EventLog]::SourceExists({source})) {{\n    \
+            $log = [System.Diagnostics.EventLog]::LogNameFromSourceName({source}, '.')\n    \
+            if ($log -ne {log}) {{\n        \
+            throw ('Source ''{{0}}'' is registered in the log ''{{1}}'', not ''{{2}}''' -f \
+            {source}, $log, {log})\n    \
+            }}\n\
+            }} else {{\n    \
+            New-EventLog -LogName {log} -Source {source}\n\
+            }}"
+        )
+        .unwrap();
+    }
+    let mut write_key = |path: &str, values: &[(&str, RegistryValue)]| {
+        let path = ps_quote(&format!(r"HKLM:\{path}"));
+        out.push('\n');
+        for (name, value) in values {
+            let (ty, value) = ps_value(value);
+            writeln!(
+                out,
+                "New-ItemProperty -Path {path} -Name {} -PropertyType {ty} -Value {value} -Force \
+                | Out-Null",
+                ps_quote(name)
+            )
+            .unwrap();
+        }
+    };
+    if let Some(log_values) = &state.log_values {
+        write_key(&state.log_key(), log_values);
+    }
+    for source in &state.sources {
+        write_key(&state.source_key(source), &state.source_values);
+    }
+    out
+}
+
+fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn ps_value(value: &RegistryValue) -> (&'static str, String) {
+    match value {
+        RegistryValue::String(value) => ("String", ps_quote(value)),
+        RegistryValue::ExpandString(value) => ("ExpandString", ps_quote(value)),
+        RegistryValue::U32(value) => ("DWord", value.to_string()),
+        RegistryValue::MultiString(values) => {
+            let values: Vec<_> = values.iter().map(|v| ps_quote(v)).collect();
+            ("MultiString", format!("@({})", values.join(", ")))
+        }
+        RegistryValue::Binary(bytes) => {
+            let bytes: Vec<_> = bytes.iter().map(|b| format!("0x{b:02x}")).collect();
+            ("Binary", format!("([byte[]]({}))", bytes.join(", ")))
+        }
+    }
+}
+
+pub(crate) fn wix_fragment(state: &DesiredState) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+        <Wix xmlns=\"http://wixtoolset.org/schemas/v4/wxs\" \
+        xmlns:util=\"http://wixtoolset.org/schemas/v4/wxs/util\">\n  <Fragment>\n",
+    );
+    let log_id = wix_id(&state.log);
+    writeln!(
+        out,
+        "    <ComponentGroup Id=\"EventLog_{log_id}\" Directory=\"TARGETDIR\">"
+    )
+    .unwrap();
+
+    if let Some(log_values) = &state.log_values {
+        writeln!(out, "      <Component Id=\"EventLog_{log_id}_Settings\">").unwrap();
+        for (i, (name, value)) in log_values.iter().enumerate() {
+            let (ty, value) = match value {
+                RegistryValue::U32(value) => ("integer", value.to_string()),
+                RegistryValue::ExpandString(value) => ("expandable", value.clone()),
+                RegistryValue::String(value) => ("string", value.clone()),
+                // Custom log settings are plain strings and numbers
+                _ => continue,
+            };
+            let key_path = if i == 0 { " KeyPath=\"yes\"" } else { "" };
+            writeln!(
+                out,
+                "        <RegistryValue Root=\"HKLM\" Key=\"{}\" Name=\"{name}\" Type=\"{ty}\" \
+                Value=\"{}\"{key_path} />",
+                xml_escape(&state.log_key()),
+                xml_escape(&value)
+            )
+            .unwrap();
+        }
+        writeln!(out, "      </Component>").unwrap();
+    }
+
+    for source in &state.sources {
+        writeln!(
+            out,
+            "      <Component Id=\"EventSource_{log_id}_{}\">",
+            wix_id(source)
+        )
+        .unwrap();
+        write!(
+            out,
+            "        <util:EventSource Log=\"{}\" Name=\"{}\"",
+            xml_escape(&state.log),
+            xml_escape(source)
+        )
+        .unwrap();
+        for (name, value) in &state.source_values {
+            match (*name, value) {
+                (
+                    "EventMessageFile" | "CategoryMessageFile" | "ParameterMessageFile",
+                    RegistryValue::String(path) | RegistryValue::ExpandString(path),
+                ) => write!(out, " {name}=\"{}\"", xml_escape(path)).unwrap(),
+                ("CategoryCount", RegistryValue::U32(count)) => {
+                    write!(out, " CategoryCount=\"{count}\"").unwrap()
+                }
+                _ => {}
+            }
+        }
+        out.push_str(
+            " SupportsErrors=\"yes\" SupportsWarnings=\"yes\" SupportsInformationals=\"yes\" \
+            KeyPath=\"yes\" />\n      </Component>\n",
+        );
+    }
+    out.push_str("    </ComponentGroup>\n  </Fragment>\n</Wix>\n");
+    out
+}
+
+/// Turns a name into a WiX identifier, which only allows ASCII letters, digits, `_` and `.`.
+fn wix_id(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+#[path = "./export_test.rs"]
+mod export_test;
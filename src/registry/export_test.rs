@@ -0,0 +1,75 @@
+use crate::{CustomLogOptions, EventLogRegistry, LogSource, MessageFiles, Retention};
+use std::{fs, path::Path};
+
+// Run with UPDATE_GOLDEN=1 to rewrite the expected files after an intended change
+fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/registry/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    assert_eq!(expected, actual, "{name} differs from the golden file");
+}
+
+fn custom_source() -> LogSource {
+    let options = CustomLogOptions::new()
+        .max_size(100 * 1024 * 1024)
+        .retention(Retention::Never)
+        .auto_backup(true)
+        .file(r"%SystemRoot%\System32\winevt\Logs\Acme.evtx");
+    let files = MessageFiles::new()
+        .event_message_file(r"%ProgramFiles%\Acme\acme.exe")
+        .event_message_file(r"C:\Acme\messages.dll")
+        .parameter_message_file(r"%SystemRoot%\System32\kernel32.dll");
+    LogSource::custom_with_options("Acme", vec!["Acme Updater"], options).with_message_files(files)
+}
+
+#[test]
+fn test_reg_file() {
+    assert_golden("custom.reg", &custom_source().to_reg_file().unwrap());
+}
+
+#[test]
+fn test_powershell() {
+    assert_golden("custom.ps1", &custom_source().to_powershell().unwrap());
+}
+
+#[test]
+fn test_application_powershell() {
+    let files = MessageFiles::new().event_message_file(r"%ProgramFiles%\Acme\acme.exe");
+    let source = LogSource::application("Acme's App").with_message_files(files);
+
+    assert_golden("application.ps1", &source.to_powershell().unwrap());
+}
+
+#[test]
+fn test_wix_fragment() {
+    assert_golden("custom.wxs", &custom_source().to_wix_fragment().unwrap());
+}
+
+#[test]
+fn test_application_reg_file() {
+    let files = MessageFiles::new().event_message_file(r#"C:\Program Files\"Acme"\acme.exe"#);
+    let source = LogSource::application("Acme").with_message_files(files);
+
+    let reg = source.to_reg_file().unwrap();
+
+    assert!(reg.starts_with(
+        "Windows Registry Editor Version 5.00\r\n\r\n\
+        [HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Services\\EventLog\\Application\\Acme]\r\n\
+        \"EventMessageFile\"=\"C:\\\\Program Files\\\\\\\"Acme\\\"\\\\acme.exe\"\r\n"
+    ));
+    assert!(reg.ends_with("\"TypesSupported\"=dword:00000007\r\n"));
+}
+
+#[test]
+fn test_export_validates() {
+    let source =
+        LogSource::custom_with_options("Acme", None, CustomLogOptions::new().max_size(1000));
+
+    assert!(source.to_reg_file().is_err());
+    assert!(source.to_powershell().is_err());
+    assert!(source.to_wix_fragment().is_err());
+}
@@ -0,0 +1,16 @@
+#Requires -RunAsAdministrator
+$ErrorActionPreference = 'Stop'
+
+if ([System.Diagnostics.EventLog]::SourceExists('Acme''s App')) {
+    $log = [System.Diagnostics.EventLog]::LogNameFromSourceName('Acme''s App', '.')
+    if ($log -ne 'Application') {
+        throw ('Source ''{0}'' is registered in the log ''{1}'', not ''{2}''' -f 'Acme''s App', $log, 'Application')
+    }
+} else {
+    New-EventLog -LogName 'Application' -Source 'Acme''s App'
+}
+
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Application\Acme''s App' -Name 'EventMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Application\Acme''s App' -Name 'CategoryMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Application\Acme''s App' -Name 'CategoryCount' -PropertyType DWord -Value 6 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Application\Acme''s App' -Name 'TypesSupported' -PropertyType DWord -Value 7 -Force | Out-Null
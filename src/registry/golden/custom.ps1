@@ -0,0 +1,36 @@
+#Requires -RunAsAdministrator
+$ErrorActionPreference = 'Stop'
+
+if ([System.Diagnostics.EventLog]::SourceExists('Acme Updater')) {
+    $log = [System.Diagnostics.EventLog]::LogNameFromSourceName('Acme Updater', '.')
+    if ($log -ne 'Acme') {
+        throw ('Source ''{0}'' is registered in the log ''{1}'', not ''{2}''' -f 'Acme Updater', $log, 'Acme')
+    }
+} else {
+    New-EventLog -LogName 'Acme' -Source 'Acme Updater'
+}
+if ([System.Diagnostics.EventLog]::SourceExists('Acme')) {
+    $log = [System.Diagnostics.EventLog]::LogNameFromSourceName('Acme', '.')
+    if ($log -ne 'Acme') {
+        throw ('Source ''{0}'' is registered in the log ''{1}'', not ''{2}''' -f 'Acme', $log, 'Acme')
+    }
+} else {
+    New-EventLog -LogName 'Acme' -Source 'Acme'
+}
+
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme' -Name 'MaxSize' -PropertyType DWord -Value 104857600 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme' -Name 'Retention' -PropertyType DWord -Value 4294967295 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme' -Name 'AutoBackupLogFiles' -PropertyType DWord -Value 1 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme' -Name 'File' -PropertyType ExpandString -Value '%SystemRoot%\System32\winevt\Logs\Acme.evtx' -Force | Out-Null
+
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme Updater' -Name 'EventMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe;C:\Acme\messages.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme Updater' -Name 'CategoryMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe;C:\Acme\messages.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme Updater' -Name 'ParameterMessageFile' -PropertyType ExpandString -Value '%SystemRoot%\System32\kernel32.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme Updater' -Name 'CategoryCount' -PropertyType DWord -Value 6 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme Updater' -Name 'TypesSupported' -PropertyType DWord -Value 7 -Force | Out-Null
+
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme' -Name 'EventMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe;C:\Acme\messages.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme' -Name 'CategoryMessageFile' -PropertyType ExpandString -Value '%ProgramFiles%\Acme\acme.exe;C:\Acme\messages.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme' -Name 'ParameterMessageFile' -PropertyType ExpandString -Value '%SystemRoot%\System32\kernel32.dll' -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme' -Name 'CategoryCount' -PropertyType DWord -Value 6 -Force | Out-Null
+New-ItemProperty -Path 'HKLM:\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme' -Name 'TypesSupported' -PropertyType DWord -Value 7 -Force | Out-Null
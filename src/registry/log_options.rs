@@ -0,0 +1,160 @@
+use super::{RegistryValue, SecurityDescriptor};
+use crate::error::RegistryError;
+use std::time::Duration;
+
+// The event log service allocates log files in 64 KB blocks
+const MAX_SIZE_INCREMENT: u32 = 0x10000;
+const DEFAULT_MAX_SIZE: u32 = 0x00080000;
+const RETAIN_FOREVER: u32 = 0xFFFFFFFF;
+
+/// How long events are kept before they may be overwritten.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Retention {
+    /// Overwrite the oldest events when the log is full.
+    #[default]
+    Overwrite,
+    /// Only overwrite events older than the given age, in whole seconds.
+    OverwriteOlderThan(Duration),
+    /// Never overwrite events. Once the log is full, new events are lost unless
+    /// [`CustomLogOptions::auto_backup`] is set.
+    Never,
+}
+
+impl Retention {
+    fn value(&self) -> Result<u32, RegistryError> {
+        match self {
+            Self::Overwrite => Ok(0),
+            Self::OverwriteOlderThan(age) => match u32::try_from(age.as_secs()) {
+                Ok(secs) if secs > 0 && secs != RETAIN_FOREVER => Ok(secs),
+                _ => Err(RegistryError::InvalidLogOption(format!(
+                    "Retention must be between 1 second and {} seconds, got {age:?}",
+                    RETAIN_FOREVER - 1
+                ))),
+            },
+            Self::Never => Ok(RETAIN_FOREVER),
+        }
+    }
+}
+
+/// Settings of a custom event log, written to its key when the log is registered.
+///
+/// Settings are updated every time the log is registered, so changing them takes effect on
+/// the next registration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomLogOptions {
+    max_size: u32,
+    retention: Retention,
+    auto_backup: bool,
+    file: Option<String>,
+    security_descriptor: Option<SecurityDescriptor>,
+}
+
+impl Default for CustomLogOptions {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+            retention: Retention::default(),
+            auto_backup: false,
+            file: None,
+            security_descriptor: None,
+        }
+    }
+}
+
+impl CustomLogOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum size of the log file in bytes, a multiple of 64 KB. Defaults to 512 KB.
+    pub fn max_size(mut self, bytes: u32) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Defaults to [`Retention::Overwrite`].
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Archives the log when it is full and starts a new one, instead of losing events.
+    /// Requires [`Retention::Never`].
+    pub fn auto_backup(mut self, enabled: bool) -> Self {
+        self.auto_backup = enabled;
+        self
+    }
+
+    /// The path of the `.evtx` file holding the log. It may contain environment variables like
+    /// `%SystemRoot%`. By default the event log service picks the path.
+    pub fn file<T: Into<String>>(mut self, path: T) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Restricts who may read, write and clear the log, stored as its `CustomSD` value. By
+    /// default the event log service grants access to the usual groups, including Event Log
+    /// Readers.
+    pub fn security_descriptor(mut self, descriptor: SecurityDescriptor) -> Self {
+        self.security_descriptor = Some(descriptor);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        if self.max_size == 0 || self.max_size % MAX_SIZE_INCREMENT != 0 {
+            return Err(RegistryError::InvalidLogOption(format!(
+                "MaxSize must be a non-zero multiple of 64 KB, got {} bytes",
+                self.max_size
+            )));
+        }
+        self.retention.value()?;
+        if self.auto_backup && self.retention != Retention::Never {
+            return Err(RegistryError::InvalidLogOption(
+                "AutoBackupLogFiles requires Retention::Never".to_owned(),
+            ));
+        }
+        if let Some(file) = &self.file {
+            let is_evtx = file
+                .rsplit_once('.')
+                .is_some_and(|(stem, ext)| !stem.is_empty() && ext.eq_ignore_ascii_case("evtx"));
+            if !is_evtx {
+                return Err(RegistryError::InvalidLogOption(format!(
+                    "File must be the path of an .evtx file, got '{file}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The values to write to the key of the log.
+    pub(crate) fn values(&self) -> Result<Vec<(&'static str, RegistryValue)>, RegistryError> {
+        let mut values = vec![
+            ("MaxSize", self.max_size.into()),
+            ("Retention", self.retention.value()?.into()),
+            ("AutoBackupLogFiles", u32::from(self.auto_backup).into()),
+        ];
+        if let Some(file) = &self.file {
+            values.push(("File", RegistryValue::ExpandString(file.clone())));
+        }
+        if let Some(descriptor) = &self.security_descriptor {
+            values.push(("CustomSD", descriptor.as_sddl().into()));
+        }
+        Ok(values)
+    }
+
+    /// The optional values that aren't set, to remove from the key of the log.
+    pub(crate) fn unset_values(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.file.is_none() {
+            names.push("File");
+        }
+        if self.security_descriptor.is_none() {
+            names.push("CustomSD");
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+#[path = "./log_options_test.rs"]
+mod log_options_test;
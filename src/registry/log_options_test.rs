@@ -0,0 +1,88 @@
+use super::*;
+
+#[test]
+fn test_default_options_are_valid() {
+    assert!(CustomLogOptions::new().validate().is_ok());
+}
+
+#[test]
+fn test_max_size_must_be_multiple_of_64k() {
+    for size in [0, 1000, 100 * 1000 * 1000] {
+        let err = CustomLogOptions::new()
+            .max_size(size)
+            .validate()
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::InvalidLogOption(_)), "{size}");
+    }
+    assert!(
+        CustomLogOptions::new()
+            .max_size(100 * 1024 * 1024)
+            .validate()
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_retention_range() {
+    for age in [Duration::ZERO, Duration::from_secs(u64::from(u32::MAX))] {
+        let options = CustomLogOptions::new().retention(Retention::OverwriteOlderThan(age));
+        assert!(options.validate().is_err(), "{age:?}");
+    }
+}
+
+#[test]
+fn test_auto_backup_requires_retention_never() {
+    let options = CustomLogOptions::new().auto_backup(true);
+    assert!(options.validate().is_err());
+    assert!(options.retention(Retention::Never).validate().is_ok());
+}
+
+#[test]
+fn test_file_must_be_evtx() {
+    for file in ["", ".evtx", r"C:\Logs\app.log"] {
+        assert!(
+            CustomLogOptions::new().file(file).validate().is_err(),
+            "{file}"
+        );
+    }
+    assert!(
+        CustomLogOptions::new()
+            .file(r"%SystemRoot%\System32\winevt\Logs\App.EVTX")
+            .validate()
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_values() {
+    let options = CustomLogOptions::new()
+        .max_size(100 * 1024 * 1024)
+        .retention(Retention::Never)
+        .auto_backup(true)
+        .file(r"D:\Logs\Test.evtx");
+
+    assert_eq!(
+        vec![
+            ("MaxSize", RegistryValue::U32(0x06400000)),
+            ("Retention", RegistryValue::U32(0xFFFFFFFF)),
+            ("AutoBackupLogFiles", RegistryValue::U32(1)),
+            (
+                "File",
+                RegistryValue::ExpandString(r"D:\Logs\Test.evtx".to_owned())
+            ),
+        ],
+        options.values().unwrap()
+    );
+}
+
+#[test]
+fn test_default_values_omit_file() {
+    let names: Vec<_> = CustomLogOptions::new()
+        .values()
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+
+    assert_eq!(vec!["MaxSize", "Retention", "AutoBackupLogFiles"], names);
+}
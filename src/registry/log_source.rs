@@ -0,0 +1,446 @@
+use super::{
+    APPLICATION, CustomLogOptions, DeregistrationReport, DeregistrationWarning, DesiredState,
+    EventLogRegistry, InMemoryRegistry, MessageFiles, REG_BASEKEY, RegistrationReport,
+    RegistrationStatus, RegistryBackend, RegistryPlan, current_exe_path, export, names,
+    source_values,
+};
+use crate::error::RegistryError;
+
+enum SourceType {
+    Application,
+    Custom {
+        sources: Vec<String>,
+        options: CustomLogOptions,
+    },
+}
+
+/// When deregistering a custom log source removes the log itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RemoveLog {
+    /// Remove the log once none of its sources are left.
+    #[default]
+    IfEmpty,
+    /// Only remove the sources of the [`LogSource`], keeping the log and the source named after
+    /// it.
+    Never,
+    /// Remove the log with every source in it, including ones registered by other
+    /// applications.
+    Force,
+}
+
+/// An event source to register, in the Application log or in a custom log.
+///
+/// [`application`](EventLogRegistry::application) and [`custom`](EventLogRegistry::custom) accept
+/// any name and report invalid ones when registering; [`try_application`](Self::try_application)
+/// and [`try_custom`](Self::try_custom) reject them right away.
+pub struct LogSource {
+    source: SourceType,
+    name: String,
+    server: Option<String>,
+    message_files: MessageFiles,
+    remove_log: RemoveLog,
+}
+
+impl LogSource {
+    /// Like [`EventLogRegistry::application`], failing if `name` is empty, contains a backslash
+    /// or control character, or is the name of a built-in log.
+    pub fn try_application(name: impl Into<String>) -> Result<Self, RegistryError> {
+        let source = Self::application(name);
+        names::validate_new_name(&source.name)?;
+        Ok(source)
+    }
+
+    /// Like [`EventLogRegistry::custom`], failing if the name of the log or of one of the sources
+    /// is invalid. See [`try_application`](Self::try_application).
+    pub fn try_custom<'a>(
+        name: impl Into<String>,
+        sources: impl Into<Option<Vec<&'a str>>>,
+    ) -> Result<Self, RegistryError> {
+        let source = Self::custom(name, sources);
+        for name in source.registered_sources() {
+            names::validate_new_name(&name)?;
+        }
+        Ok(source)
+    }
+
+    /// Like [`EventLogRegistry::custom`], with settings for the custom log.
+    pub fn custom_with_options<'a>(
+        name: impl Into<String>,
+        sources: impl Into<Option<Vec<&'a str>>>,
+        options: CustomLogOptions,
+    ) -> Self {
+        let name = name.into();
+        let sources = sources.into().unwrap_or_default();
+        let sources = sources.into_iter().map(|s| s.into()).collect();
+        Self {
+            source: SourceType::Custom { sources, options },
+            name,
+            server: None,
+            message_files: MessageFiles::default(),
+            remove_log: RemoveLog::default(),
+        }
+    }
+
+    /// Registers the source on a remote computer instead of the local machine.
+    ///
+    /// The message files must exist on the server for messages to be displayed there. They are
+    /// not checked before registering.
+    pub fn with_server<T: Into<String>>(mut self, server: T) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// Registers these message files instead of the current executable, e.g. when registering
+    /// from an installer rather than from the application that logs.
+    pub fn with_message_files(mut self, message_files: MessageFiles) -> Self {
+        self.message_files = message_files;
+        self
+    }
+
+    /// Whether deregistering a custom log source removes the log. Defaults to
+    /// [`RemoveLog::IfEmpty`], so sources registered by other applications are kept.
+    pub fn with_remove_log(mut self, remove_log: RemoveLog) -> Self {
+        self.remove_log = remove_log;
+        self
+    }
+
+    fn resolve_message_files(&self, check_files: bool) -> Result<MessageFiles, RegistryError> {
+        let mut message_files = self.message_files.clone();
+        if message_files.is_empty() {
+            message_files = message_files.event_message_file(current_exe_path()?);
+        }
+        message_files.validate(check_files)?;
+        Ok(message_files)
+    }
+
+    /// The keys and values a registration of the source consists of.
+    pub(crate) fn desired_state(&self, check_files: bool) -> Result<DesiredState, RegistryError> {
+        let source_values = source_values(&self.resolve_message_files(check_files)?);
+        let state = match &self.source {
+            SourceType::Application => DesiredState {
+                log: APPLICATION.to_owned(),
+                log_values: None,
+                unset_log_values: Vec::new(),
+                sources: self.registered_sources(),
+                source_values,
+            },
+            SourceType::Custom { options, .. } => {
+                options.validate()?;
+                DesiredState {
+                    log: self.name.clone(),
+                    log_values: Some(options.values()?),
+                    unset_log_values: options.unset_values(),
+                    sources: self.registered_sources(),
+                    source_values,
+                }
+            }
+        };
+        names::validate(&state, self.is_custom())?;
+        Ok(state)
+    }
+
+    fn is_custom(&self) -> bool {
+        matches!(self.source, SourceType::Custom { .. })
+    }
+
+    /// The sources registered in the log, which for a custom log include one named after it.
+    fn registered_sources(&self) -> Vec<String> {
+        match &self.source {
+            SourceType::Application => vec![self.name.clone()],
+            SourceType::Custom { sources, .. } => {
+                let mut sources = sources.clone();
+                if !sources.iter().any(|s| s.eq_ignore_ascii_case(&self.name)) {
+                    sources.push(self.name.clone());
+                }
+                sources
+            }
+        }
+    }
+
+    /// Checks the names, message files and log options without accessing the registry.
+    ///
+    /// Registering also fails if the names clash with logs or sources already in the registry.
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        self.desired_state(false)?;
+        Ok(())
+    }
+
+    /// Plans the registration against `backend` instead of the Windows registry.
+    pub fn plan_with(&self, backend: &impl RegistryBackend) -> Result<RegistryPlan, RegistryError> {
+        self.plan_in(backend, self.server.is_none())
+    }
+
+    /// Plans the registration as if nothing was registered yet, without reading any registry or
+    /// checking that the message files exist.
+    pub fn plan_offline(&self) -> Result<RegistryPlan, RegistryError> {
+        self.plan_in(&InMemoryRegistry::new(), false)
+    }
+
+    /// Plans the deregistration as if only this source was registered, without reading any
+    /// registry.
+    pub fn plan_deregister_offline(&self) -> Result<RegistryPlan, RegistryError> {
+        let registry = InMemoryRegistry::new();
+        self.plan_in(&registry, false)?.execute(&registry)?;
+        self.plan_deregister_with(&registry)
+    }
+
+    fn plan_in(
+        &self,
+        backend: &impl RegistryBackend,
+        check_files: bool,
+    ) -> Result<RegistryPlan, RegistryError> {
+        let state = self.desired_state(check_files)?;
+        names::check_conflicts(backend, &state, self.is_custom())?;
+        let mut plan = RegistryPlan::default();
+
+        if let Some(log_values) = &state.log_values {
+            let log_key = state.log_key();
+            plan.create_key_if_missing(backend, &log_key)?;
+            for (name, value) in log_values {
+                plan.set_value_if_changed(backend, &log_key, name, value.clone())?;
+            }
+            for name in &state.unset_log_values {
+                plan.delete_value_if_present(backend, &log_key, name)?;
+            }
+        }
+
+        // Existing sources are repaired, e.g. after an upgrade moved the message files
+        for source in &state.sources {
+            let source_key = state.source_key(source);
+            plan.create_key_if_missing(backend, &source_key)?;
+            for (name, value) in &state.source_values {
+                plan.set_value_if_changed(backend, &source_key, name, value.clone())?;
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Registers the source in `backend` instead of the Windows registry.
+    pub fn register_with(
+        &self,
+        backend: &impl RegistryBackend,
+    ) -> Result<RegistrationReport, RegistryError> {
+        let plan = self.plan_with(backend)?;
+        plan.execute(backend)?;
+        Ok(RegistrationReport {
+            changes: plan.changes,
+        })
+    }
+
+    /// Renders the registration as a `.reg` file for `regedit`.
+    ///
+    /// The file must be saved as UTF-16LE with a byte order mark if it contains characters
+    /// outside of ASCII. Message files are not checked, as they usually don't exist yet on the
+    /// machine building the installer.
+    pub fn to_reg_file(&self) -> Result<String, RegistryError> {
+        Ok(export::reg_file(&self.desired_state(false)?))
+    }
+
+    /// Renders the registration as a PowerShell script using `New-EventLog` and
+    /// `New-ItemProperty`, to be run elevated. The script fails if a source is already
+    /// registered in another log.
+    pub fn to_powershell(&self) -> Result<String, RegistryError> {
+        Ok(export::powershell(&self.desired_state(false)?))
+    }
+
+    /// Renders the registration as a WiX v4 fragment with a `<util:EventSource>` per source.
+    pub fn to_wix_fragment(&self) -> Result<String, RegistryError> {
+        Ok(export::wix_fragment(&self.desired_state(false)?))
+    }
+
+    /// Reads the registration of the source from `backend` instead of the Windows registry.
+    ///
+    /// For a custom log this is the first of its sources, or the one named after the log if
+    /// none were given. Use [`source_status_with`](Self::source_status_with) for the others.
+    pub fn status_with(
+        &self,
+        backend: &impl RegistryBackend,
+    ) -> Result<RegistrationStatus, RegistryError> {
+        let source = match &self.source {
+            SourceType::Custom { sources, .. } => sources.first().unwrap_or(&self.name),
+            SourceType::Application => &self.name,
+        };
+        self.source_status_with(backend, source)
+    }
+
+    /// Reads the registration of `source`, expected in the log of this [`LogSource`], from
+    /// `backend`.
+    pub fn source_status_with(
+        &self,
+        backend: &impl RegistryBackend,
+        source: &str,
+    ) -> Result<RegistrationStatus, RegistryError> {
+        let expected_log = match &self.source {
+            SourceType::Application => APPLICATION,
+            SourceType::Custom { .. } => &self.name,
+        };
+        RegistrationStatus::read(backend, source, expected_log, self.server.is_none())
+    }
+
+    /// Plans the deregistration against `backend` instead of the Windows registry.
+    pub fn plan_deregister_with(
+        &self,
+        backend: &impl RegistryBackend,
+    ) -> Result<RegistryPlan, RegistryError> {
+        Ok(self.deregistration(backend)?.0)
+    }
+
+    /// Removes the source from `backend` instead of the Windows registry.
+    pub fn deregister_with(
+        self,
+        backend: &impl RegistryBackend,
+    ) -> Result<DeregistrationReport, RegistryError> {
+        let (plan, report) = self.deregistration(backend)?;
+        plan.execute(backend)?;
+        Ok(report)
+    }
+
+    fn deregistration(
+        &self,
+        backend: &impl RegistryBackend,
+    ) -> Result<(RegistryPlan, DeregistrationReport), RegistryError> {
+        let mut plan = RegistryPlan::default();
+        let mut report = DeregistrationReport::default();
+        if !self.is_custom() {
+            let source_key = format!(r"{REG_BASEKEY}\{APPLICATION}\{}", self.name);
+            if !backend.key_exists(&source_key)? {
+                return Err(RegistryError::KeyNotFound(source_key));
+            }
+            plan.delete_tree(source_key);
+            report.removed_sources.push(self.name.clone());
+            return Ok((plan, report));
+        }
+        let sources = self.registered_sources();
+
+        let log_key = format!(r"{REG_BASEKEY}\{}", self.name);
+        let mut subkeys = backend.subkeys(&log_key)?;
+        // The source named after the log is shared by every registration in it, so it is only
+        // removed together with the log
+        let log_source = subkeys
+            .iter()
+            .position(|source| source.eq_ignore_ascii_case(&self.name))
+            .map(|i| subkeys.remove(i));
+        let (own, others): (Vec<_>, Vec<_>) = subkeys
+            .into_iter()
+            .partition(|source| sources.iter().any(|s| s.eq_ignore_ascii_case(source)));
+        let remove_log = match self.remove_log {
+            RemoveLog::IfEmpty => others.is_empty(),
+            RemoveLog::Never => false,
+            RemoveLog::Force => true,
+        };
+
+        if remove_log {
+            // Counted before the key is gone, after which the name resolves to Application
+            if let Some(count) = backend.record_count(&self.name)?.filter(|&count| count > 0) {
+                report.warnings.push(DeregistrationWarning::LogHasRecords {
+                    log: self.name.clone(),
+                    count,
+                });
+            }
+            if !others.is_empty() {
+                report
+                    .warnings
+                    .push(DeregistrationWarning::OtherSourcesRemoved {
+                        log: self.name.clone(),
+                        sources: others.clone(),
+                    });
+            }
+            plan.delete_tree(log_key);
+            report.log_removed = true;
+            report.removed_sources = own.into_iter().chain(log_source).chain(others).collect();
+        } else {
+            for source in &own {
+                plan.delete_tree(format!(r"{log_key}\{source}"));
+            }
+            if !others.is_empty() {
+                report.warnings.push(DeregistrationWarning::LogNotEmpty {
+                    log: self.name.clone(),
+                    sources: others,
+                });
+            }
+            report.removed_sources = own;
+        }
+        Ok((plan, report))
+    }
+
+    #[cfg(windows)]
+    fn backend(&self) -> super::WindowsRegistry {
+        match &self.server {
+            Some(server) => super::WindowsRegistry::remote(server),
+            None => super::WindowsRegistry::new(),
+        }
+    }
+}
+
+impl EventLogRegistry for LogSource {
+    fn application(name: impl Into<String>) -> Self {
+        Self {
+            source: SourceType::Application,
+            name: name.into(),
+            server: None,
+            message_files: MessageFiles::default(),
+            remove_log: RemoveLog::default(),
+        }
+    }
+
+    fn custom<'a>(name: impl Into<String>, sources: impl Into<Option<Vec<&'a str>>>) -> Self {
+        Self::custom_with_options(name, sources, CustomLogOptions::default())
+    }
+
+    #[cfg(windows)]
+    fn register(&self) -> Result<RegistrationReport, RegistryError> {
+        self.register_with(&self.backend())
+    }
+
+    // There is no event log to register with
+    #[cfg(not(windows))]
+    fn register(&self) -> Result<RegistrationReport, RegistryError> {
+        Ok(RegistrationReport::default())
+    }
+
+    #[cfg(windows)]
+    fn deregister(self) -> Result<DeregistrationReport, RegistryError> {
+        let backend = self.backend();
+        self.deregister_with(&backend)
+    }
+
+    #[cfg(not(windows))]
+    fn deregister(self) -> Result<DeregistrationReport, RegistryError> {
+        Ok(DeregistrationReport::default())
+    }
+
+    #[cfg(windows)]
+    fn plan(&self) -> Result<RegistryPlan, RegistryError> {
+        self.plan_with(&self.backend())
+    }
+
+    #[cfg(not(windows))]
+    fn plan(&self) -> Result<RegistryPlan, RegistryError> {
+        Ok(RegistryPlan::default())
+    }
+
+    #[cfg(windows)]
+    fn plan_deregister(&self) -> Result<RegistryPlan, RegistryError> {
+        self.plan_deregister_with(&self.backend())
+    }
+
+    #[cfg(not(windows))]
+    fn plan_deregister(&self) -> Result<RegistryPlan, RegistryError> {
+        Ok(RegistryPlan::default())
+    }
+
+    #[cfg(windows)]
+    fn status(&self) -> Result<RegistrationStatus, RegistryError> {
+        self.status_with(&self.backend())
+    }
+
+    #[cfg(not(windows))]
+    fn status(&self) -> Result<RegistrationStatus, RegistryError> {
+        Ok(RegistrationStatus::not_registered())
+    }
+}
+
+#[cfg(test)]
+#[path = "./log_source_test.rs"]
+mod log_source_test;
@@ -0,0 +1,422 @@
+use super::*;
+use crate::{InMemoryRegistry, MessageFiles, RegistryChange, RegistryValue, Retention, eventmsgs};
+
+const BASE_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog";
+
+fn exe_path() -> RegistryValue {
+    RegistryValue::String(current_exe_path().unwrap())
+}
+
+fn assert_source_values(registry: &InMemoryRegistry, key: &str) {
+    assert_eq!(
+        vec![
+            ("CategoryCount".to_owned(), eventmsgs::CATEGORY_COUNT.into()),
+            ("CategoryMessageFile".to_owned(), exe_path()),
+            ("EventMessageFile".to_owned(), exe_path()),
+            ("TypesSupported".to_owned(), RegistryValue::U32(7)),
+        ],
+        registry.values(key)
+    );
+}
+
+#[test]
+fn test_register_application() {
+    let registry = InMemoryRegistry::new();
+
+    LogSource::application("Test App")
+        .register_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            "SYSTEM".to_owned(),
+            r"SYSTEM\CurrentControlSet".to_owned(),
+            r"SYSTEM\CurrentControlSet\Services".to_owned(),
+            BASE_KEY.to_owned(),
+            format!(r"{BASE_KEY}\Application"),
+            format!(r"{BASE_KEY}\Application\Test App"),
+        ],
+        registry.keys()
+    );
+    assert_source_values(&registry, &format!(r"{BASE_KEY}\Application\Test App"));
+}
+
+#[test]
+fn test_register_application_is_idempotent() {
+    let registry = InMemoryRegistry::new();
+    let source = LogSource::application("Test App");
+
+    let first = source.register_with(&registry).unwrap();
+    let second = source.register_with(&registry).unwrap();
+
+    // The source key plus its four values
+    assert_eq!(5, first.changes.len());
+    assert!(second.is_unchanged());
+    assert_eq!("no changes", second.to_string());
+}
+
+#[test]
+fn test_register_repairs_stale_application_source() {
+    let registry = InMemoryRegistry::new();
+    let key = format!(r"{BASE_KEY}\Application\Test App");
+    LogSource::application("Test App")
+        .register_with(&registry)
+        .unwrap();
+    registry
+        .set_value(&key, "EventMessageFile", &r"C:\Old\app.exe".into())
+        .unwrap();
+    registry
+        .set_value(&key, "CategoryCount", &RegistryValue::U32(1))
+        .unwrap();
+
+    let report = LogSource::application("Test App")
+        .register_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            RegistryChange::SetValue {
+                path: key.clone(),
+                name: "EventMessageFile".to_owned(),
+                old: Some(r"C:\Old\app.exe".into()),
+                new: exe_path(),
+            },
+            RegistryChange::SetValue {
+                path: key.clone(),
+                name: "CategoryCount".to_owned(),
+                old: Some(RegistryValue::U32(1)),
+                new: eventmsgs::CATEGORY_COUNT.into(),
+            },
+        ],
+        report.changes
+    );
+    assert_source_values(&registry, &key);
+}
+
+#[test]
+fn test_register_custom() {
+    let registry = InMemoryRegistry::new();
+
+    LogSource::custom("Test Log", vec!["Extra Source"])
+        .register_with(&registry)
+        .unwrap();
+
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    assert_eq!(
+        vec!["Extra Source".to_owned(), "Test Log".to_owned()],
+        registry.subkeys(&log_key).unwrap()
+    );
+    assert_eq!(
+        vec![
+            ("AutoBackupLogFiles".to_owned(), RegistryValue::U32(0)),
+            ("MaxSize".to_owned(), RegistryValue::U32(0x00080000)),
+            ("Retention".to_owned(), RegistryValue::U32(0)),
+        ],
+        registry.values(&log_key)
+    );
+    assert_source_values(&registry, &format!(r"{log_key}\Test Log"));
+    assert_source_values(&registry, &format!(r"{log_key}\Extra Source"));
+    assert!(
+        !registry
+            .key_exists(&format!(r"{BASE_KEY}\Application"))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_register_custom_is_idempotent() {
+    let registry = InMemoryRegistry::new();
+    let source = LogSource::custom("Test Log", None);
+    source.register_with(&registry).unwrap();
+    let keys = registry.keys();
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    let values = registry.values(&log_key);
+
+    let report = source.register_with(&registry).unwrap();
+
+    assert!(report.is_unchanged());
+    assert_eq!(keys, registry.keys());
+    assert_eq!(values, registry.values(&log_key));
+}
+
+#[test]
+fn test_register_custom_completes_partial_registration() {
+    let registry = InMemoryRegistry::new();
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    registry
+        .create_key(&format!(r"{log_key}\Test Log"))
+        .unwrap();
+
+    LogSource::custom("Test Log", vec!["Extra Source"])
+        .register_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        Some(RegistryValue::U32(0x00080000)),
+        registry.get_value(&log_key, "MaxSize").unwrap()
+    );
+    assert_source_values(&registry, &format!(r"{log_key}\Test Log"));
+    assert_source_values(&registry, &format!(r"{log_key}\Extra Source"));
+}
+
+#[test]
+fn test_register_custom_updates_options() {
+    let registry = InMemoryRegistry::new();
+    LogSource::custom("Test Log", None)
+        .register_with(&registry)
+        .unwrap();
+
+    let options = CustomLogOptions::new()
+        .max_size(100 * 1024 * 1024)
+        .retention(Retention::Never)
+        .auto_backup(true);
+    let report = LogSource::custom_with_options("Test Log", None, options)
+        .register_with(&registry)
+        .unwrap();
+
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    let changed: Vec<_> = report
+        .changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect();
+    assert_eq!(
+        vec![
+            format!(r"change {log_key}\MaxSize from U32(524288) to U32(104857600)"),
+            format!(r"change {log_key}\Retention from U32(0) to U32(4294967295)"),
+            format!(r"change {log_key}\AutoBackupLogFiles from U32(0) to U32(1)"),
+        ],
+        changed
+    );
+    assert_eq!(
+        vec![
+            ("AutoBackupLogFiles".to_owned(), RegistryValue::U32(1)),
+            ("MaxSize".to_owned(), RegistryValue::U32(0x06400000)),
+            ("Retention".to_owned(), RegistryValue::U32(0xFFFFFFFF)),
+        ],
+        registry.values(&log_key)
+    );
+}
+
+#[test]
+fn test_register_custom_rejects_invalid_options() {
+    let registry = InMemoryRegistry::new();
+    let options = CustomLogOptions::new().max_size(1000);
+
+    let err = LogSource::custom_with_options("Test Log", None, options)
+        .register_with(&registry)
+        .unwrap_err();
+
+    assert!(matches!(err, RegistryError::InvalidLogOption(_)));
+    assert!(registry.keys().is_empty());
+}
+
+#[test]
+fn test_register_message_files() {
+    let registry = InMemoryRegistry::new();
+    let files = MessageFiles::new()
+        .event_message_file("%CARGO_MANIFEST_DIR%/Cargo.toml")
+        .parameter_message_file("%CARGO_MANIFEST_DIR%/build.rs");
+
+    LogSource::application("Test App")
+        .with_message_files(files)
+        .register_with(&registry)
+        .unwrap();
+
+    let key = format!(r"{BASE_KEY}\Application\Test App");
+    let manifest = RegistryValue::ExpandString("%CARGO_MANIFEST_DIR%/Cargo.toml".to_owned());
+    assert_eq!(
+        Some(manifest.clone()),
+        registry.get_value(&key, "EventMessageFile").unwrap()
+    );
+    assert_eq!(
+        Some(manifest),
+        registry.get_value(&key, "CategoryMessageFile").unwrap()
+    );
+    assert_eq!(
+        Some(RegistryValue::ExpandString(
+            "%CARGO_MANIFEST_DIR%/build.rs".to_owned()
+        )),
+        registry.get_value(&key, "ParameterMessageFile").unwrap()
+    );
+}
+
+#[test]
+fn test_register_rejects_missing_message_file() {
+    let registry = InMemoryRegistry::new();
+    let files = MessageFiles::new().event_message_file(r"C:\Missingpp.exe");
+
+    let err = LogSource::custom("Test Log", None)
+        .with_message_files(files.clone())
+        .register_with(&registry)
+        .unwrap_err();
+
+    assert!(matches!(err, RegistryError::InvalidMessageFile { .. }));
+    assert!(registry.keys().is_empty());
+
+    // Files on a remote server can't be checked
+    LogSource::custom("Test Log", None)
+        .with_message_files(files)
+        .with_server("server")
+        .register_with(&registry)
+        .unwrap();
+}
+
+#[test]
+fn test_deregister_application_keeps_other_sources() {
+    let registry = InMemoryRegistry::new();
+    LogSource::application("Test App")
+        .register_with(&registry)
+        .unwrap();
+    LogSource::application("Other App")
+        .register_with(&registry)
+        .unwrap();
+
+    LogSource::application("Test App")
+        .deregister_with(&registry)
+        .unwrap();
+
+    assert_eq!(
+        vec!["Other App".to_owned()],
+        registry
+            .subkeys(&format!(r"{BASE_KEY}\Application"))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_deregister_custom_removes_log() {
+    let registry = InMemoryRegistry::new();
+    LogSource::custom("Test Log", vec!["Extra Source"])
+        .register_with(&registry)
+        .unwrap();
+
+    let report = LogSource::custom("Test Log", vec!["Extra Source"])
+        .deregister_with(&registry)
+        .unwrap();
+
+    assert!(registry.subkeys(BASE_KEY).unwrap().is_empty());
+    assert_eq!(
+        DeregistrationReport {
+            removed_sources: vec!["Extra Source".to_owned(), "Test Log".to_owned()],
+            log_removed: true,
+            warnings: vec![],
+        },
+        report
+    );
+}
+
+#[test]
+fn test_deregister_custom_keeps_other_sources() {
+    let registry = InMemoryRegistry::new();
+    LogSource::custom("Test Log", vec!["Other Product"])
+        .register_with(&registry)
+        .unwrap();
+
+    let report = LogSource::custom("Test Log", vec!["Updater"])
+        .deregister_with(&registry)
+        .unwrap();
+
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    assert_eq!(
+        vec!["Other Product".to_owned(), "Test Log".to_owned()],
+        registry.subkeys(&log_key).unwrap()
+    );
+    assert!(report.removed_sources.is_empty());
+    assert!(!report.log_removed);
+    assert_eq!(
+        vec![DeregistrationWarning::LogNotEmpty {
+            log: "Test Log".to_owned(),
+            sources: vec!["Other Product".to_owned()]
+        }],
+        report.warnings
+    );
+}
+
+#[test]
+fn test_deregister_custom_keeps_shared_log_source() {
+    let registry = InMemoryRegistry::new();
+    let sibling = LogSource::custom("Test Log", vec!["Sibling"]);
+    sibling.register_with(&registry).unwrap();
+    LogSource::custom("Test Log", vec!["Updater"])
+        .register_with(&registry)
+        .unwrap();
+
+    let report = LogSource::custom("Test Log", vec!["Updater"])
+        .deregister_with(&registry)
+        .unwrap();
+
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    assert_eq!(
+        vec!["Sibling".to_owned(), "Test Log".to_owned()],
+        registry.subkeys(&log_key).unwrap()
+    );
+    assert_eq!(vec!["Updater".to_owned()], report.removed_sources);
+    assert!(!report.log_removed);
+    assert!(sibling.status_with(&registry).unwrap().is_healthy());
+    assert!(
+        sibling
+            .source_status_with(&registry, "Test Log")
+            .unwrap()
+            .is_healthy()
+    );
+}
+
+#[test]
+fn test_deregister_custom_never_removes_log() {
+    let registry = InMemoryRegistry::new();
+    LogSource::custom("Test Log", vec!["Test App"])
+        .register_with(&registry)
+        .unwrap();
+
+    let report = LogSource::custom("Test Log", vec!["Test App"])
+        .with_remove_log(RemoveLog::Never)
+        .deregister_with(&registry)
+        .unwrap();
+
+    // The source named after the log goes with the log
+    let log_key = format!(r"{BASE_KEY}\Test Log");
+    assert_eq!(
+        vec!["Test Log".to_owned()],
+        registry.subkeys(&log_key).unwrap()
+    );
+    assert_eq!(vec!["Test App".to_owned()], report.removed_sources);
+    assert!(!report.log_removed);
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn test_deregister_custom_force() {
+    let registry = InMemoryRegistry::new();
+    LogSource::custom("Test Log", vec!["Other Product"])
+        .register_with(&registry)
+        .unwrap();
+    registry.set_record_count("Test Log", 42);
+
+    let report = LogSource::custom("Test Log", None)
+        .with_remove_log(RemoveLog::Force)
+        .deregister_with(&registry)
+        .unwrap();
+
+    assert!(registry.subkeys(BASE_KEY).unwrap().is_empty());
+    assert!(report.log_removed);
+    let warnings: Vec<_> = report.warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        vec![
+            "the removed log 'Test Log' still contained 42 records",
+            "removing the log 'Test Log' also removed these sources: Other Product",
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn test_deregister_missing_source() {
+    let registry = InMemoryRegistry::new();
+
+    let err = LogSource::application("Test App")
+        .deregister_with(&registry)
+        .unwrap_err();
+
+    assert!(matches!(err, RegistryError::KeyNotFound(_)));
+}
@@ -0,0 +1,106 @@
+use super::{RegistryValue, expand_environment};
+use crate::error::RegistryError;
+use std::path::Path;
+
+/// The modules holding the message tables of a source.
+///
+/// Each kind of message file may list several modules, which are written to the registry
+/// separated by semicolons. Paths may contain environment variables like `%ProgramFiles%`, in
+/// which case they are written as `REG_EXPAND_SZ` values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageFiles {
+    event: Vec<String>,
+    category: Vec<String>,
+    parameter: Vec<String>,
+}
+
+impl MessageFiles {
+    /// No message files, which registers the current executable.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module to `EventMessageFile`.
+    pub fn event_message_file<T: Into<String>>(mut self, path: T) -> Self {
+        self.event.push(path.into());
+        self
+    }
+
+    /// Adds a module to `CategoryMessageFile`. Defaults to the event message files.
+    pub fn category_message_file<T: Into<String>>(mut self, path: T) -> Self {
+        self.category.push(path.into());
+        self
+    }
+
+    /// Adds a module to `ParameterMessageFile`, which is only written if set.
+    pub fn parameter_message_file<T: Into<String>>(mut self, path: T) -> Self {
+        self.parameter.push(path.into());
+        self
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.event.is_empty() && self.category.is_empty() && self.parameter.is_empty()
+    }
+
+    /// Checks the paths can be written to the registry. With `check_exists`, every path must
+    /// also point to an existing file on this machine once variables are expanded.
+    pub fn validate(&self, check_exists: bool) -> Result<(), RegistryError> {
+        if self.event.is_empty() {
+            return Err(RegistryError::InvalidMessageFile {
+                path: String::new(),
+                reason: "no event message file is set".to_owned(),
+            });
+        }
+        for path in self
+            .event
+            .iter()
+            .chain(&self.category)
+            .chain(&self.parameter)
+        {
+            let reason = if path.trim().is_empty() {
+                "the path is empty"
+            } else if path.contains(';') {
+                "the path contains ';', which separates modules"
+            } else if check_exists && !Path::new(&expand_environment(path)).is_file() {
+                "the file does not exist"
+            } else {
+                continue;
+            };
+            return Err(RegistryError::InvalidMessageFile {
+                path: path.clone(),
+                reason: reason.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The values to write under the source key.
+    pub(crate) fn values(&self) -> Vec<(&'static str, RegistryValue)> {
+        let category = if self.category.is_empty() {
+            &self.event
+        } else {
+            &self.category
+        };
+        let mut values = vec![
+            ("EventMessageFile", registry_value(&self.event)),
+            ("CategoryMessageFile", registry_value(category)),
+        ];
+        if !self.parameter.is_empty() {
+            values.push(("ParameterMessageFile", registry_value(&self.parameter)));
+        }
+        values
+    }
+}
+
+fn registry_value(paths: &[String]) -> RegistryValue {
+    let value = paths.join(";");
+    if value.contains('%') {
+        RegistryValue::ExpandString(value)
+    } else {
+        RegistryValue::String(value)
+    }
+}
+
+#[cfg(test)]
+#[path = "./message_files_test.rs"]
+mod message_files_test;
@@ -0,0 +1,85 @@
+use super::*;
+
+const MANIFEST: &str = "%CARGO_MANIFEST_DIR%/Cargo.toml";
+
+#[test]
+fn test_category_defaults_to_event_files() {
+    let files = MessageFiles::new()
+        .event_message_file(r"C:\App\app.exe")
+        .event_message_file(r"C:\App\messages.dll");
+
+    assert_eq!(
+        vec![
+            (
+                "EventMessageFile",
+                RegistryValue::String(r"C:\App\app.exe;C:\App\messages.dll".to_owned())
+            ),
+            (
+                "CategoryMessageFile",
+                RegistryValue::String(r"C:\App\app.exe;C:\App\messages.dll".to_owned())
+            ),
+        ],
+        files.values()
+    );
+}
+
+#[test]
+fn test_variables_are_expand_strings() {
+    let files = MessageFiles::new()
+        .event_message_file(r"%ProgramFiles%\App\app.exe")
+        .category_message_file(r"C:\App\categories.dll")
+        .parameter_message_file(r"%SystemRoot%\System32\kernel32.dll");
+
+    assert_eq!(
+        vec![
+            (
+                "EventMessageFile",
+                RegistryValue::ExpandString(r"%ProgramFiles%\App\app.exe".to_owned())
+            ),
+            (
+                "CategoryMessageFile",
+                RegistryValue::String(r"C:\App\categories.dll".to_owned())
+            ),
+            (
+                "ParameterMessageFile",
+                RegistryValue::ExpandString(r"%SystemRoot%\System32\kernel32.dll".to_owned())
+            ),
+        ],
+        files.values()
+    );
+}
+
+#[test]
+fn test_validate_expands_variables() {
+    let files = MessageFiles::new().event_message_file(MANIFEST);
+
+    assert!(files.validate(true).is_ok());
+}
+
+#[test]
+fn test_validate_rejects_invalid_paths() {
+    for (files, check_exists) in [
+        (MessageFiles::new(), false),
+        (MessageFiles::new().event_message_file(" "), false),
+        (MessageFiles::new().event_message_file("a.dll;b.dll"), false),
+        (
+            MessageFiles::new()
+                .event_message_file(MANIFEST)
+                .parameter_message_file("missing.dll"),
+            true,
+        ),
+    ] {
+        let err = files.validate(check_exists).unwrap_err();
+        assert!(
+            matches!(err, RegistryError::InvalidMessageFile { .. }),
+            "{files:?}"
+        );
+    }
+}
+
+#[test]
+fn test_validate_skips_existence_check() {
+    let files = MessageFiles::new().event_message_file(r"\\server\share\missing.dll");
+
+    assert!(files.validate(false).is_ok());
+}
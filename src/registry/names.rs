@@ -0,0 +1,98 @@
+use super::{DesiredState, REG_BASEKEY, RegistryBackend};
+use crate::error::RegistryError;
+
+// Logs that exist on every machine; their names can't be used for sources or custom logs
+const BUILTIN_LOGS: [&str; 3] = ["Application", "Security", "System"];
+
+// The event log service tells custom logs apart by the first 8 characters of their names
+const UNIQUE_PREFIX_LEN: usize = 8;
+
+/// Checks that `name` can be used as the name of a source or log.
+pub(crate) fn validate_name(name: &str) -> Result<(), RegistryError> {
+    if name.is_empty() {
+        return Err(RegistryError::EmptyName);
+    }
+    // The registry uses backslashes to separate keys
+    if let Some(character) = name.chars().find(|&c| c == '\\' || c.is_control()) {
+        return Err(RegistryError::InvalidNameCharacter {
+            name: name.to_owned(),
+            character,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `name` can be used for a source or a custom log, which can't be named like one of
+/// the built-in logs.
+pub(crate) fn validate_new_name(name: &str) -> Result<(), RegistryError> {
+    validate_name(name)?;
+    if is_builtin_log(name) {
+        return Err(RegistryError::ReservedName(name.to_owned()));
+    }
+    Ok(())
+}
+
+/// Checks the names of a registration without looking at the registry.
+pub(crate) fn validate(state: &DesiredState, custom: bool) -> Result<(), RegistryError> {
+    if custom {
+        validate_new_name(&state.log)?;
+    } else {
+        validate_name(&state.log)?;
+    }
+    for source in &state.sources {
+        validate_new_name(source)?;
+    }
+    Ok(())
+}
+
+/// Checks that the names of a registration don't clash with the logs and sources in `backend`.
+pub(crate) fn check_conflicts(
+    backend: &impl RegistryBackend,
+    state: &DesiredState,
+    custom: bool,
+) -> Result<(), RegistryError> {
+    if !backend.key_exists(REG_BASEKEY)? {
+        return Ok(());
+    }
+    let other_logs = backend
+        .subkeys(REG_BASEKEY)?
+        .into_iter()
+        .filter(|log| !log.eq_ignore_ascii_case(&state.log));
+    for log in other_logs {
+        if custom && unique_prefix(&log) == unique_prefix(&state.log) {
+            return Err(RegistryError::LogPrefixCollision {
+                log: state.log.clone(),
+                existing: log,
+            });
+        }
+        for source in &state.sources {
+            if source.eq_ignore_ascii_case(&log) {
+                return Err(RegistryError::ReservedName(source.clone()));
+            }
+            if backend.key_exists(&format!(r"{REG_BASEKEY}\{log}\{source}"))? {
+                return Err(RegistryError::SourceInOtherLog {
+                    name: source.clone(),
+                    log,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_builtin_log(name: &str) -> bool {
+    BUILTIN_LOGS
+        .iter()
+        .any(|log| log.eq_ignore_ascii_case(name))
+}
+
+fn unique_prefix(name: &str) -> String {
+    name.chars()
+        .take(UNIQUE_PREFIX_LEN)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+#[path = "./names_test.rs"]
+mod names_test;
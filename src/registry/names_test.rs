@@ -0,0 +1,127 @@
+use super::*;
+use crate::{EventLogRegistry, InMemoryRegistry, LogSource, MessageFiles};
+
+fn files() -> MessageFiles {
+    MessageFiles::new().event_message_file("%CARGO_MANIFEST_DIR%/Cargo.toml")
+}
+
+fn application(name: &str) -> LogSource {
+    LogSource::application(name).with_message_files(files())
+}
+
+fn custom(name: &str, sources: Vec<&str>) -> LogSource {
+    LogSource::custom(name, sources).with_message_files(files())
+}
+
+#[test]
+fn test_invalid_characters() {
+    let err = application(r"My\App").validate().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RegistryError::InvalidNameCharacter {
+                character: '\\',
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err = custom(r"Log\Name", vec![]).validate().unwrap_err();
+    assert!(
+        matches!(&err, RegistryError::InvalidNameCharacter { name, .. } if name == r"Log\Name"),
+        "{err}"
+    );
+
+    let err = custom("Log", vec!["Tab\tSource"]).validate().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RegistryError::InvalidNameCharacter {
+                character: '\t',
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err = application("").validate().unwrap_err();
+    assert!(matches!(err, RegistryError::EmptyName), "{err}");
+}
+
+#[test]
+fn test_reserved_names() {
+    for source in [application("System"), custom("Security", vec![])] {
+        let err = source.validate().unwrap_err();
+        assert!(matches!(err, RegistryError::ReservedName(_)), "{err}");
+    }
+    assert!(application("My App").validate().is_ok());
+}
+
+#[test]
+fn test_fallible_constructors() {
+    let err = LogSource::try_application("application").err().unwrap();
+    assert!(matches!(err, RegistryError::ReservedName(_)), "{err}");
+    let err = LogSource::try_custom("Audit", vec![r"Web\App"])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, RegistryError::InvalidNameCharacter { .. }),
+        "{err}"
+    );
+    let err = LogSource::try_custom("", None).err().unwrap();
+    assert!(matches!(err, RegistryError::EmptyName), "{err}");
+
+    assert!(LogSource::try_application("My App").is_ok());
+    assert!(LogSource::try_custom("Audit", vec!["Web"]).is_ok());
+}
+
+#[test]
+fn test_log_prefix_collision() {
+    let registry = InMemoryRegistry::new();
+    custom("Contoso Audit", vec![])
+        .register_with(&registry)
+        .unwrap();
+
+    let err = custom("CONTOSO Billing", vec![])
+        .register_with(&registry)
+        .unwrap_err();
+
+    assert_eq!(
+        "Custom log 'CONTOSO Billing' starts with the same 8 characters as the existing log \
+        'Contoso Audit'; the event log service can't tell them apart",
+        err.to_string()
+    );
+    assert!(
+        custom("Contoso Audit", vec!["Web"])
+            .register_with(&registry)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_source_in_other_log() {
+    let registry = InMemoryRegistry::new();
+    custom("Audit", vec!["Web"])
+        .register_with(&registry)
+        .unwrap();
+
+    let err = application("Web").plan_with(&registry).unwrap_err();
+
+    assert!(
+        matches!(&err, RegistryError::SourceInOtherLog { name, log } if name == "Web" && log == "Audit"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_source_named_like_log() {
+    let registry = InMemoryRegistry::new();
+    custom("Audit", vec![]).register_with(&registry).unwrap();
+
+    let err = custom("Billing", vec!["audit"])
+        .plan_with(&registry)
+        .unwrap_err();
+
+    assert!(matches!(err, RegistryError::ReservedName(_)), "{err}");
+}
@@ -0,0 +1,279 @@
+use super::{RegistryBackend, RegistryValue};
+use crate::error::RegistryError;
+use std::fmt;
+
+/// A single registry operation, planned or made while registering or deregistering a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "op", rename_all = "snake_case"))]
+pub enum RegistryChange {
+    CreateKey {
+        path: String,
+    },
+    /// Adds a value, or replaces one that differs from the desired value.
+    SetValue {
+        path: String,
+        name: String,
+        old: Option<RegistryValue>,
+        new: RegistryValue,
+    },
+    /// Removes a value that is no longer wanted.
+    DeleteValue {
+        path: String,
+        name: String,
+        old: RegistryValue,
+    },
+    /// Removes a key with all its subkeys and values.
+    DeleteTree {
+        path: String,
+    },
+}
+
+impl fmt::Display for RegistryChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateKey { path } => write!(f, "create key {path}"),
+            Self::SetValue {
+                path,
+                name,
+                old: None,
+                new,
+            } => write!(f, r"set {path}\{name} to {new:?}"),
+            Self::SetValue {
+                path,
+                name,
+                old: Some(old),
+                new,
+            } => write!(f, r"change {path}\{name} from {old:?} to {new:?}"),
+            Self::DeleteValue { path, name, old } => write!(f, r"delete {path}\{name} ({old:?})"),
+            Self::DeleteTree { path } => write!(f, "delete key {path} with all subkeys"),
+        }
+    }
+}
+
+/// The registry operations a registration or deregistration would perform, in order.
+///
+/// Returned by [`EventLogRegistry::plan`](super::EventLogRegistry::plan) and
+/// [`EventLogRegistry::plan_deregister`](super::EventLogRegistry::plan_deregister) to review the
+/// changes before making them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegistryPlan {
+    pub changes: Vec<RegistryChange>,
+}
+
+impl RegistryPlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn create_key_if_missing(
+        &mut self,
+        backend: &impl RegistryBackend,
+        path: &str,
+    ) -> Result<(), RegistryError> {
+        if !backend.key_exists(path)? {
+            self.changes.push(RegistryChange::CreateKey {
+                path: path.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_value_if_changed(
+        &mut self,
+        backend: &impl RegistryBackend,
+        path: &str,
+        name: &str,
+        value: RegistryValue,
+    ) -> Result<(), RegistryError> {
+        let old = backend.get_value(path, name)?;
+        if old.as_ref() != Some(&value) {
+            self.changes.push(RegistryChange::SetValue {
+                path: path.to_owned(),
+                name: name.to_owned(),
+                old,
+                new: value,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn delete_value_if_present(
+        &mut self,
+        backend: &impl RegistryBackend,
+        path: &str,
+        name: &str,
+    ) -> Result<(), RegistryError> {
+        if let Some(old) = backend.get_value(path, name)? {
+            self.changes.push(RegistryChange::DeleteValue {
+                path: path.to_owned(),
+                name: name.to_owned(),
+                old,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn delete_tree(&mut self, path: String) {
+        self.changes.push(RegistryChange::DeleteTree { path });
+    }
+
+    /// Performs the operations against `backend`, all or nothing.
+    ///
+    /// Runs inside a registry transaction when the backend supports them. Otherwise every change
+    /// is undone if a later one fails; [`RegistryError::RollbackFailed`] is returned if undoing
+    /// them fails as well.
+    pub fn execute(&self, backend: &impl RegistryBackend) -> Result<(), RegistryError> {
+        if let Some(transaction) = backend.transaction()? {
+            // Dropping the transaction on failure discards its changes
+            self.apply(&&*transaction, &mut Vec::new())?;
+            return transaction.commit();
+        }
+        let mut undo = Vec::new();
+        match self.apply(backend, &mut undo) {
+            Ok(()) => Ok(()),
+            Err(cause) => match roll_back(backend, undo) {
+                Ok(()) => Err(cause),
+                Err(rollback) => Err(RegistryError::RollbackFailed {
+                    cause: Box::new(cause),
+                    rollback: Box::new(rollback),
+                }),
+            },
+        }
+    }
+
+    fn apply(
+        &self,
+        backend: &impl RegistryBackend,
+        undo: &mut Vec<Undo>,
+    ) -> Result<(), RegistryError> {
+        for change in &self.changes {
+            match change {
+                RegistryChange::CreateKey { path } => {
+                    let created = first_missing_key(backend, path)?;
+                    backend.create_key(path)?;
+                    undo.extend(created.map(Undo::RemoveTree));
+                }
+                RegistryChange::SetValue {
+                    path, name, new, ..
+                } => {
+                    let old = backend.get_value(path, name)?;
+                    backend.set_value(path, name, new)?;
+                    undo.push(Undo::RestoreValue {
+                        path: path.clone(),
+                        name: name.clone(),
+                        old,
+                    });
+                }
+                RegistryChange::DeleteValue { path, name, .. } => {
+                    let old = backend.get_value(path, name)?;
+                    backend.delete_value(path, name)?;
+                    undo.push(Undo::RestoreValue {
+                        path: path.clone(),
+                        name: name.clone(),
+                        old,
+                    });
+                }
+                RegistryChange::DeleteTree { path } => {
+                    let mut keys = Vec::new();
+                    snapshot(backend, path, &mut keys)?;
+                    backend.remove_tree(path)?;
+                    undo.push(Undo::RestoreTree(keys));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reverts a change that has been made.
+enum Undo {
+    RemoveTree(String),
+    RestoreValue {
+        path: String,
+        name: String,
+        old: Option<RegistryValue>,
+    },
+    /// Keys with their values, parents before their subkeys.
+    RestoreTree(Vec<(String, Vec<(String, RegistryValue)>)>),
+}
+
+/// The topmost key that creating `path` would add, if any.
+fn first_missing_key(
+    backend: &impl RegistryBackend,
+    path: &str,
+) -> Result<Option<String>, RegistryError> {
+    let path = path.trim_matches('\\');
+    let ancestors = path
+        .match_indices('\\')
+        .map(|(end, _)| &path[..end])
+        .chain([path]);
+    for key in ancestors {
+        if !backend.key_exists(key)? {
+            return Ok(Some(key.to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn snapshot(
+    backend: &impl RegistryBackend,
+    path: &str,
+    keys: &mut Vec<(String, Vec<(String, RegistryValue)>)>,
+) -> Result<(), RegistryError> {
+    let mut values = Vec::new();
+    for name in backend.value_names(path)? {
+        if let Some(value) = backend.get_value(path, &name)? {
+            values.push((name, value));
+        }
+    }
+    keys.push((path.to_owned(), values));
+    for subkey in backend.subkeys(path)? {
+        snapshot(backend, &format!(r"{path}\{subkey}"), keys)?;
+    }
+    Ok(())
+}
+
+fn roll_back(backend: &impl RegistryBackend, undo: Vec<Undo>) -> Result<(), RegistryError> {
+    for step in undo.into_iter().rev() {
+        match step {
+            Undo::RemoveTree(path) => backend.remove_tree(&path)?,
+            Undo::RestoreValue {
+                path,
+                name,
+                old: Some(old),
+            } => backend.set_value(&path, &name, &old)?,
+            Undo::RestoreValue {
+                path,
+                name,
+                old: None,
+            } => backend.delete_value(&path, &name)?,
+            Undo::RestoreTree(keys) => {
+                for (path, values) in keys {
+                    backend.create_key(&path)?;
+                    for (name, value) in values {
+                        backend.set_value(&path, &name, &value)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for RegistryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./plan_test.rs"]
+mod plan_test;
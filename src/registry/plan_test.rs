@@ -0,0 +1,425 @@
+use super::*;
+use crate::{
+    CustomLogOptions, EventLogRegistry, InMemoryRegistry, LogSource, MessageFiles,
+    RegistryTransaction, SecurityDescriptor,
+};
+use std::{cell::Cell, ops::Range};
+
+const SOURCE_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\Application\Test App";
+
+/// Fails the writes whose zero-based index is in `failing`, optionally inside a transaction.
+struct FaultyRegistry {
+    registry: InMemoryRegistry,
+    writes: Cell<usize>,
+    failing: Range<usize>,
+    transactional: bool,
+}
+
+impl FaultyRegistry {
+    fn new(registry: InMemoryRegistry, failing: Range<usize>) -> Self {
+        Self {
+            registry,
+            writes: Cell::new(0),
+            failing,
+            transactional: false,
+        }
+    }
+
+    fn write(&self) -> Result<(), RegistryError> {
+        let index = self.writes.replace(self.writes.get() + 1);
+        if self.failing.contains(&index) {
+            return Err(RegistryError::KeyNotFound(format!(
+                "injected fault #{index}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn contents(&self) -> Vec<(String, Vec<(String, RegistryValue)>)> {
+        contents(&self.registry)
+    }
+}
+
+fn contents(registry: &InMemoryRegistry) -> Vec<(String, Vec<(String, RegistryValue)>)> {
+    registry
+        .keys()
+        .into_iter()
+        .map(|key| {
+            let values = registry.values(&key);
+            (key, values)
+        })
+        .collect()
+}
+
+fn contents_of(keys: &[(String, Vec<(String, RegistryValue)>)]) -> InMemoryRegistry {
+    let registry = InMemoryRegistry::new();
+    for (key, values) in keys {
+        registry.create_key(key).unwrap();
+        for (name, value) in values {
+            registry.set_value(key, name, value).unwrap();
+        }
+    }
+    registry
+}
+
+fn copy(from: &InMemoryRegistry, to: &InMemoryRegistry) {
+    for key in to.keys() {
+        let _ = to.remove_tree(&key);
+    }
+    for (key, values) in contents(from) {
+        to.create_key(&key).unwrap();
+        for (name, value) in values {
+            to.set_value(&key, &name, &value).unwrap();
+        }
+    }
+}
+
+impl RegistryBackend for FaultyRegistry {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+        self.registry.key_exists(path)
+    }
+
+    fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+        self.write()?;
+        self.registry.create_key(path)
+    }
+
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+        self.write()?;
+        self.registry.remove_tree(path)
+    }
+
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.registry.subkeys(path)
+    }
+
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError> {
+        self.registry.get_value(path, name)
+    }
+
+    fn set_value(
+        &self,
+        path: &str,
+        name: &str,
+        value: &RegistryValue,
+    ) -> Result<(), RegistryError> {
+        self.write()?;
+        self.registry.set_value(path, name, value)
+    }
+
+    fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+        self.write()?;
+        self.registry.delete_value(path, name)
+    }
+
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.registry.value_names(path)
+    }
+
+    fn transaction(&self) -> Result<Option<Box<dyn RegistryTransaction + '_>>, RegistryError> {
+        if !self.transactional {
+            return Ok(None);
+        }
+        let staged = FaultyRegistry::new(InMemoryRegistry::new(), self.failing.clone());
+        copy(&self.registry, &staged.registry);
+        Ok(Some(Box::new(StagedRegistry {
+            target: &self.registry,
+            staged,
+        })))
+    }
+}
+
+/// Changes made to a copy of the registry that replaces the original on commit.
+struct StagedRegistry<'a> {
+    target: &'a InMemoryRegistry,
+    staged: FaultyRegistry,
+}
+
+impl RegistryBackend for StagedRegistry<'_> {
+    fn key_exists(&self, path: &str) -> Result<bool, RegistryError> {
+        self.staged.key_exists(path)
+    }
+
+    fn create_key(&self, path: &str) -> Result<(), RegistryError> {
+        self.staged.create_key(path)
+    }
+
+    fn remove_tree(&self, path: &str) -> Result<(), RegistryError> {
+        self.staged.remove_tree(path)
+    }
+
+    fn subkeys(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.staged.subkeys(path)
+    }
+
+    fn get_value(&self, path: &str, name: &str) -> Result<Option<RegistryValue>, RegistryError> {
+        self.staged.get_value(path, name)
+    }
+
+    fn set_value(
+        &self,
+        path: &str,
+        name: &str,
+        value: &RegistryValue,
+    ) -> Result<(), RegistryError> {
+        self.staged.set_value(path, name, value)
+    }
+
+    fn delete_value(&self, path: &str, name: &str) -> Result<(), RegistryError> {
+        self.staged.delete_value(path, name)
+    }
+
+    fn value_names(&self, path: &str) -> Result<Vec<String>, RegistryError> {
+        self.staged.value_names(path)
+    }
+}
+
+impl RegistryTransaction for StagedRegistry<'_> {
+    fn commit(self: Box<Self>) -> Result<(), RegistryError> {
+        copy(&self.staged.registry, self.target);
+        Ok(())
+    }
+}
+
+fn source() -> LogSource {
+    let files = MessageFiles::new().event_message_file("%CARGO_MANIFEST_DIR%/Cargo.toml");
+    LogSource::application("Test App").with_message_files(files)
+}
+
+#[test]
+fn test_plan_does_not_write() {
+    let registry = InMemoryRegistry::new();
+
+    let plan = source().plan_with(&registry).unwrap();
+
+    assert_eq!(
+        RegistryChange::CreateKey {
+            path: SOURCE_KEY.to_owned()
+        },
+        plan.changes[0]
+    );
+    assert_eq!(5, plan.changes.len());
+    assert!(registry.keys().is_empty());
+}
+
+#[test]
+fn test_register_executes_plan() {
+    let registry = InMemoryRegistry::new();
+    let plan = source().plan_with(&registry).unwrap();
+
+    let report = source().register_with(&registry).unwrap();
+
+    assert_eq!(plan.changes, report.changes);
+    assert!(source().plan_with(&registry).unwrap().is_empty());
+}
+
+#[test]
+fn test_plan_display() {
+    let registry = InMemoryRegistry::new();
+    source().register_with(&registry).unwrap();
+    registry
+        .set_value(SOURCE_KEY, "TypesSupported", &RegistryValue::U32(1))
+        .unwrap();
+
+    let plan = source().plan_with(&registry).unwrap();
+
+    assert_eq!(
+        format!("change {SOURCE_KEY}\\TypesSupported from U32(1) to U32(7)\n"),
+        plan.to_string()
+    );
+    assert_eq!("no changes", RegistryPlan::default().to_string());
+}
+
+#[test]
+fn test_plan_deregister() {
+    let registry = InMemoryRegistry::new();
+    source().register_with(&registry).unwrap();
+
+    let plan = source().plan_deregister_with(&registry).unwrap();
+
+    assert_eq!(
+        vec![RegistryChange::DeleteTree {
+            path: SOURCE_KEY.to_owned()
+        }],
+        plan.changes
+    );
+    assert!(registry.key_exists(SOURCE_KEY).unwrap());
+
+    plan.execute(&registry).unwrap();
+    assert!(!registry.key_exists(SOURCE_KEY).unwrap());
+}
+
+fn custom_source(sources: &[&str]) -> LogSource {
+    let files = MessageFiles::new().event_message_file("%CARGO_MANIFEST_DIR%/Cargo.toml");
+    LogSource::custom_with_options("Test Log", sources.to_vec(), CustomLogOptions::new())
+        .with_message_files(files)
+}
+
+#[test]
+fn test_plan_deletes_unset_log_values() {
+    let registry = InMemoryRegistry::new();
+    let descriptor = SecurityDescriptor::builder().read("BU").build().unwrap();
+    let options = CustomLogOptions::new()
+        .file(r"%SystemRoot%\Logs\test.evtx")
+        .security_descriptor(descriptor.clone());
+    let files = MessageFiles::new().event_message_file("%CARGO_MANIFEST_DIR%/Cargo.toml");
+    LogSource::custom_with_options("Test Log", None, options)
+        .with_message_files(files)
+        .register_with(&registry)
+        .unwrap();
+
+    let plan = custom_source(&[]).plan_with(&registry).unwrap();
+
+    let log_key = r"SYSTEM\CurrentControlSet\Services\EventLog\Test Log";
+    assert_eq!(
+        vec![
+            RegistryChange::DeleteValue {
+                path: log_key.to_owned(),
+                name: "File".to_owned(),
+                old: RegistryValue::ExpandString(r"%SystemRoot%\Logs\test.evtx".to_owned()),
+            },
+            RegistryChange::DeleteValue {
+                path: log_key.to_owned(),
+                name: "CustomSD".to_owned(),
+                old: descriptor.as_sddl().into(),
+            },
+        ],
+        plan.changes
+    );
+    assert_eq!(
+        format!(r#"delete {log_key}\File (ExpandString("%SystemRoot%\\Logs\\test.evtx"))"#),
+        plan.changes[0].to_string()
+    );
+
+    plan.execute(&registry).unwrap();
+    assert_eq!(None, registry.get_value(log_key, "File").unwrap());
+    assert_eq!(None, registry.get_value(log_key, "CustomSD").unwrap());
+    assert!(custom_source(&[]).plan_with(&registry).unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_register_rolls_back_new_keys() {
+    let registry = InMemoryRegistry::new();
+    registry
+        .create_key(r"SYSTEM\CurrentControlSet\Services\EventLog")
+        .unwrap();
+    let before = contents(&registry);
+    let writes = custom_source(&["A", "B"])
+        .plan_with(&registry)
+        .unwrap()
+        .changes
+        .len();
+
+    for failing in 0..writes {
+        let faulty = FaultyRegistry::new(contents_of(&before), failing..failing + 1);
+
+        let err = custom_source(&["A", "B"])
+            .register_with(&faulty)
+            .unwrap_err();
+
+        assert!(matches!(err, RegistryError::KeyNotFound(_)), "{err}");
+        assert_eq!(before, faulty.contents(), "write #{failing} failed");
+    }
+}
+
+#[test]
+fn test_failed_register_restores_changed_values() {
+    let registry = InMemoryRegistry::new();
+    source().register_with(&registry).unwrap();
+    registry
+        .set_value(SOURCE_KEY, "TypesSupported", &RegistryValue::U32(1))
+        .unwrap();
+    registry.delete_value(SOURCE_KEY, "CategoryCount").unwrap();
+    let before = contents(&registry);
+    let faulty = FaultyRegistry::new(registry, 1..2);
+
+    assert_eq!(2, source().plan_with(&faulty).unwrap().changes.len());
+    source().register_with(&faulty).unwrap_err();
+
+    assert_eq!(before, faulty.contents());
+}
+
+#[test]
+fn test_failed_deregister_restores_removed_keys() {
+    let registry = InMemoryRegistry::new();
+    custom_source(&["A", "B", "C"])
+        .register_with(&registry)
+        .unwrap();
+    let before = contents(&registry);
+    let plan = custom_source(&["A", "B"])
+        .plan_deregister_with(&registry)
+        .unwrap();
+    let last = plan.changes.len() - 1;
+    let faulty = FaultyRegistry::new(registry, last..last + 1);
+
+    plan.execute(&faulty).unwrap_err();
+
+    assert_eq!(before, faulty.contents());
+}
+
+#[test]
+fn test_failed_rollback() {
+    let faulty = FaultyRegistry::new(InMemoryRegistry::new(), 1..usize::MAX);
+
+    let err = source().register_with(&faulty).unwrap_err();
+
+    assert!(matches!(err, RegistryError::RollbackFailed { .. }), "{err}");
+    assert!(faulty.registry.key_exists(SOURCE_KEY).unwrap());
+}
+
+#[test]
+fn test_failed_transaction_is_discarded() {
+    let mut faulty = FaultyRegistry::new(InMemoryRegistry::new(), 3..usize::MAX);
+    faulty.transactional = true;
+
+    let err = source().register_with(&faulty).unwrap_err();
+
+    assert!(matches!(err, RegistryError::KeyNotFound(_)), "{err}");
+    assert!(faulty.registry.keys().is_empty());
+
+    faulty.failing = 0..0;
+    source().register_with(&faulty).unwrap();
+    assert!(faulty.registry.key_exists(SOURCE_KEY).unwrap());
+}
+
+#[test]
+fn test_offline_plans() {
+    let source = LogSource::application("Test App")
+        .with_message_files(MessageFiles::new().event_message_file(r"C:\Missing\app.exe"));
+
+    let plan = source.plan_offline().unwrap();
+    assert_eq!(5, plan.changes.len());
+
+    assert_eq!(
+        vec![RegistryChange::DeleteTree {
+            path: SOURCE_KEY.to_owned()
+        }],
+        source.plan_deregister_offline().unwrap().changes
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_plan_serializes() {
+    let plan = RegistryPlan {
+        changes: vec![
+            RegistryChange::CreateKey {
+                path: "A".to_owned(),
+            },
+            RegistryChange::SetValue {
+                path: "A".to_owned(),
+                name: "File".to_owned(),
+                old: None,
+                new: RegistryValue::ExpandString("%SystemRoot%\\a.evtx".to_owned()),
+            },
+        ],
+    };
+
+    let json = serde_json::to_string(&plan).unwrap();
+
+    assert_eq!(
+        r#"{"changes":[{"op":"create_key","path":"A"},{"op":"set_value","path":"A","name":"File","old":null,"new":{"type":"ExpandString","data":"%SystemRoot%\\a.evtx"}}]}"#,
+        json
+    );
+    assert_eq!(plan, serde_json::from_str(&json).unwrap());
+}
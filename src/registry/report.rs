@@ -0,0 +1,69 @@
+use super::RegistryChange;
+use std::fmt;
+
+/// What [`EventLogRegistry::register`](super::EventLogRegistry::register) changed.
+///
+/// Values that already match the desired state are left alone, so registering a source twice
+/// reports no changes the second time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistrationReport {
+    pub changes: Vec<RegistryChange>,
+}
+
+impl RegistrationReport {
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for RegistrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_unchanged() {
+            return write!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Something worth knowing about a deregistration that still succeeded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeregistrationWarning {
+    /// The removed log still held records, which can no longer be viewed by log name.
+    LogHasRecords { log: String, count: u32 },
+    /// The log was kept because other sources are still registered in it.
+    LogNotEmpty { log: String, sources: Vec<String> },
+    /// Sources that don't belong to the [`LogSource`](super::LogSource) were removed along
+    /// with the log.
+    OtherSourcesRemoved { log: String, sources: Vec<String> },
+}
+
+impl fmt::Display for DeregistrationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LogHasRecords { log, count } => {
+                write!(f, "the removed log '{log}' still contained {count} records")
+            }
+            Self::LogNotEmpty { log, sources } => write!(
+                f,
+                "the log '{log}' was kept for its other sources: {}",
+                sources.join(", ")
+            ),
+            Self::OtherSourcesRemoved { log, sources } => write!(
+                f,
+                "removing the log '{log}' also removed these sources: {}",
+                sources.join(", ")
+            ),
+        }
+    }
+}
+
+/// What [`EventLogRegistry::deregister`](super::EventLogRegistry::deregister) removed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeregistrationReport {
+    pub removed_sources: Vec<String>,
+    pub log_removed: bool,
+    pub warnings: Vec<DeregistrationWarning>,
+}
@@ -0,0 +1,327 @@
+use crate::error::RegistryError;
+use std::{fmt, str::FromStr};
+
+// Access rights of event logs, see "Event Logging Security" in the Windows documentation
+const ELF_LOGFILE_READ: u32 = 0x1;
+const ELF_LOGFILE_WRITE: u32 = 0x2;
+const ELF_LOGFILE_CLEAR: u32 = 0x4;
+const SYSTEM_ACE: &str = "(A;;0xf0007;;;SY)";
+
+const SID_ALIASES: &[&str] = &[
+    "AA", "AC", "AN", "AO", "AP", "AS", "AU", "BA", "BG", "BO", "BU", "CA", "CD", "CG", "CN", "CO",
+    "CY", "DA", "DC", "DD", "DG", "DU", "EA", "ED", "EK", "ER", "ES", "HA", "HI", "IS", "IU", "KA",
+    "LA", "LG", "LS", "LU", "LW", "ME", "MP", "MU", "NO", "NS", "NU", "OW", "PA", "PO", "PS", "PU",
+    "RA", "RC", "RD", "RE", "RM", "RO", "RS", "RU", "SA", "SI", "SO", "SS", "SU", "SY", "UD", "WD",
+    "WR",
+];
+const DACL_ACE_TYPES: &[&str] = &["A", "D", "OA", "OD", "XA", "XD", "ZA"];
+const SACL_ACE_TYPES: &[&str] = &["AU", "AL", "OU", "OL", "ML", "XU", "SP", "RA"];
+const ACE_FLAGS: &[&str] = &["CI", "OI", "NP", "IO", "ID", "SA", "FA", "TP", "CR"];
+const ACE_RIGHTS: &[&str] = &[
+    "GA", "GR", "GW", "GX", "RC", "SD", "WD", "WO", "RP", "WP", "CC", "DC", "LC", "SW", "LO", "DT",
+    "CR", "FA", "FR", "FW", "FX", "KA", "KR", "KW", "KX", "NR", "NW", "NX",
+];
+const ACL_FLAGS: &[&str] = &["NO_ACCESS_CONTROL", "P", "AI", "AR"];
+
+/// Who may read, write and clear a custom event log, stored as its `CustomSD` value.
+///
+/// Create one from an SDDL string with [`SecurityDescriptor::parse`] or with
+/// [`SecurityDescriptor::builder`]. The syntax is checked without calling into Windows, so a
+/// typo is reported before anything is written to the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityDescriptor {
+    sddl: String,
+}
+
+impl SecurityDescriptor {
+    pub fn parse<T: Into<String>>(sddl: T) -> Result<Self, RegistryError> {
+        let sddl = sddl.into();
+        if let Err((offset, reason)) = Parser::new(&sddl).descriptor() {
+            return Err(RegistryError::InvalidSecurityDescriptor {
+                sddl,
+                offset,
+                reason,
+            });
+        }
+        Ok(Self { sddl })
+    }
+
+    pub fn builder() -> SecurityDescriptorBuilder {
+        SecurityDescriptorBuilder::default()
+    }
+
+    pub fn as_sddl(&self) -> &str {
+        &self.sddl
+    }
+}
+
+impl FromStr for SecurityDescriptor {
+    type Err = RegistryError;
+
+    fn from_str(sddl: &str) -> Result<Self, Self::Err> {
+        Self::parse(sddl)
+    }
+}
+
+impl fmt::Display for SecurityDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sddl)
+    }
+}
+
+/// Grants read, write and clear access to a custom event log per account.
+///
+/// Accounts are given as SIDs like `S-1-5-20` or SDDL aliases like `BA`. `LocalSystem` always
+/// gets full access; everyone else not granted access can't use the log, so remember to let the
+/// account of the application write to it.
+#[derive(Clone, Debug, Default)]
+pub struct SecurityDescriptorBuilder {
+    grants: Vec<(String, u32)>,
+}
+
+impl SecurityDescriptorBuilder {
+    pub fn read<T: Into<String>>(self, sid: T) -> Self {
+        self.grant(sid.into(), ELF_LOGFILE_READ)
+    }
+
+    pub fn write<T: Into<String>>(self, sid: T) -> Self {
+        self.grant(sid.into(), ELF_LOGFILE_WRITE)
+    }
+
+    pub fn clear<T: Into<String>>(self, sid: T) -> Self {
+        self.grant(sid.into(), ELF_LOGFILE_CLEAR)
+    }
+
+    fn grant(mut self, sid: String, access: u32) -> Self {
+        match self.grants.iter_mut().find(|(granted, _)| *granted == sid) {
+            Some((_, mask)) => *mask |= access,
+            None => self.grants.push((sid, access)),
+        }
+        self
+    }
+
+    /// Fails if one of the SIDs is invalid.
+    pub fn build(&self) -> Result<SecurityDescriptor, RegistryError> {
+        let aces = self
+            .grants
+            .iter()
+            .map(|(sid, mask)| format!("(A;;{mask:#x};;;{sid})"))
+            .collect::<String>();
+        SecurityDescriptor::parse(format!("O:BAG:SYD:{SYSTEM_ACE}{aces}"))
+    }
+}
+
+type ParseResult<T> = Result<T, (usize, String)>;
+
+/// Checks the syntax of an SDDL string, reporting the byte offset of the first error.
+struct Parser<'a> {
+    sddl: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(sddl: &'a str) -> Self {
+        Self { sddl, offset: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.sddl[self.offset..]
+    }
+
+    fn error<T>(&self, offset: usize, reason: impl Into<String>) -> ParseResult<T> {
+        Err((offset, reason.into()))
+    }
+
+    fn descriptor(&mut self) -> ParseResult<()> {
+        if self.sddl.is_empty() {
+            return self.error(0, "the descriptor is empty");
+        }
+        let mut seen = String::new();
+        while !self.rest().is_empty() {
+            let start = self.offset;
+            let Some(component) = self.rest().strip_prefix(['O', 'G', 'D', 'S']) else {
+                return self.error(start, "expected one of O:, G:, D: or S:");
+            };
+            if !component.starts_with(':') {
+                return self.error(start, "expected one of O:, G:, D: or S:");
+            }
+            let kind = &self.sddl[start..start + 1];
+            if seen.contains(kind) {
+                return self.error(start, format!("{kind}: appears more than once"));
+            }
+            seen.push_str(kind);
+            self.offset += 2;
+            match kind {
+                "O" | "G" => {
+                    let end = self.component_end();
+                    self.sid(end)?;
+                }
+                "D" => self.acl(DACL_ACE_TYPES)?,
+                _ => self.acl(SACL_ACE_TYPES)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Where the current component ends, i.e. the offset of the next `X:`.
+    fn component_end(&self) -> usize {
+        let rest = self.rest().as_bytes();
+        (0..rest.len())
+            .find(|&i| {
+                matches!(rest[i], b'O' | b'G' | b'D' | b'S') && rest.get(i + 1) == Some(&b':')
+            })
+            .map_or(self.sddl.len(), |i| self.offset + i)
+    }
+
+    fn sid(&mut self, end: usize) -> ParseResult<()> {
+        let start = self.offset;
+        let sid = &self.sddl[start..end];
+        self.offset = end;
+        if SID_ALIASES.contains(&sid) || is_sid_string(sid) {
+            Ok(())
+        } else if sid.is_empty() {
+            self.error(start, "expected a SID")
+        } else {
+            self.error(start, format!("'{sid}' is neither a SID nor an SDDL alias"))
+        }
+    }
+
+    fn acl(&mut self, ace_types: &[&str]) -> ParseResult<()> {
+        while !self.rest().is_empty() && !self.rest().starts_with('(') {
+            if self.component_end() == self.offset {
+                return Ok(());
+            }
+            match ACL_FLAGS.iter().find(|flag| self.rest().starts_with(*flag)) {
+                Some(flag) => self.offset += flag.len(),
+                None => return self.error(self.offset, "expected an ACL flag or an ACE"),
+            }
+        }
+        while self.rest().starts_with('(') {
+            self.ace(ace_types)?;
+        }
+        Ok(())
+    }
+
+    fn ace(&mut self, ace_types: &[&str]) -> ParseResult<()> {
+        let start = self.offset;
+        let Some(len) = ace_len(self.rest()) else {
+            return self.error(start, "the ACE is missing its closing ')'");
+        };
+        let mut fields = Vec::new();
+        let mut field_start = start + 1;
+        for part in self.sddl[start + 1..start + len - 1].splitn(7, ';') {
+            fields.push((field_start, part));
+            field_start += part.len() + 1;
+        }
+        self.offset = start + len;
+        if fields.len() < 6 {
+            return self.error(start, "an ACE needs 6 fields separated by ';'");
+        }
+
+        let (offset, ace_type) = fields[0];
+        if !ace_types.contains(&ace_type) {
+            let reason = if DACL_ACE_TYPES.contains(&ace_type) || SACL_ACE_TYPES.contains(&ace_type)
+            {
+                format!("ACE type '{ace_type}' is not allowed in this ACL")
+            } else {
+                format!("unknown ACE type '{ace_type}'")
+            };
+            return self.error(offset, reason);
+        }
+        let (offset, flags) = fields[1];
+        if !is_token_list(flags, ACE_FLAGS) {
+            return self.error(offset, format!("invalid ACE flags '{flags}'"));
+        }
+        let (offset, rights) = fields[2];
+        if !is_rights(rights) {
+            return self.error(offset, format!("invalid access rights '{rights}'"));
+        }
+        for &(offset, guid) in &fields[3..5] {
+            if !guid.is_empty() && !is_guid(guid) {
+                return self.error(offset, format!("invalid GUID '{guid}'"));
+            }
+        }
+        if let Some(&(offset, _)) = fields.get(6) {
+            if !matches!(ace_type, "XA" | "XD" | "XU" | "ZA" | "RA") {
+                return self.error(offset - 1, "an ACE of this type has only 6 fields");
+            }
+        }
+        let (offset, sid) = fields[5];
+        let end = offset + sid.len();
+        let resume = self.offset;
+        self.offset = offset;
+        self.sid(end)?;
+        self.offset = resume;
+        Ok(())
+    }
+}
+
+/// The length of the ACE at the start of `s`, including conditions nested in parentheses.
+fn ace_len(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_sid_string(sid: &str) -> bool {
+    let Some(rest) = sid.strip_prefix("S-1-") else {
+        return false;
+    };
+    let mut parts = rest.split('-');
+    let authority = parts.next().unwrap_or_default();
+    let authority_valid = match authority.strip_prefix("0x") {
+        Some(hex) => !hex.is_empty() && u64::from_str_radix(hex, 16).is_ok_and(|a| a < 1 << 48),
+        None => authority.parse::<u64>().is_ok_and(|a| a < 1 << 32),
+    };
+    let sub_authorities = parts.collect::<Vec<_>>();
+    authority_valid
+        && sub_authorities.len() <= 15
+        && sub_authorities
+            .iter()
+            .all(|part| !part.starts_with('+') && part.parse::<u32>().is_ok())
+}
+
+/// Whether `s` is a concatenation of two-letter tokens from `tokens`.
+fn is_token_list(s: &str, tokens: &[&str]) -> bool {
+    s.len() % 2 == 0
+        && (0..s.len())
+            .step_by(2)
+            .all(|i| s.get(i..i + 2).is_some_and(|token| tokens.contains(&token)))
+}
+
+fn is_rights(rights: &str) -> bool {
+    if let Some(hex) = rights
+        .strip_prefix("0x")
+        .or_else(|| rights.strip_prefix("0X"))
+    {
+        return !hex.is_empty() && u32::from_str_radix(hex, 16).is_ok();
+    }
+    if rights.bytes().all(|b| b.is_ascii_digit()) {
+        return rights.parse::<u32>().is_ok();
+    }
+    is_token_list(rights, ACE_RIGHTS)
+}
+
+fn is_guid(guid: &str) -> bool {
+    let parts = guid.split('-').collect::<Vec<_>>();
+    parts.len() == 5
+        && parts
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(part, len)| part.len() == len && part.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+#[path = "./security_test.rs"]
+mod security_test;
@@ -0,0 +1,103 @@
+use super::*;
+use crate::{CustomLogOptions, InMemoryRegistry, LogSource, RegistryBackend, RegistryValue};
+
+// The descriptor Windows uses for the Application log
+const APPLICATION_SDDL: &str = "O:BAG:SYD:(A;;0xf0007;;;SY)(A;;0x7;;;BA)(A;;0x3;;;BO)\
+    (A;;0x5;;;SO)(A;;0x1;;;IU)(A;;0x3;;;SU)(A;;0x1;;;S-1-5-3)(A;;0x2;;;S-1-5-33)\
+    (A;;0x1;;;S-1-5-32-573)";
+
+fn offset_of(sddl: &str) -> usize {
+    match SecurityDescriptor::parse(sddl).unwrap_err() {
+        RegistryError::InvalidSecurityDescriptor { offset, .. } => offset,
+        err => panic!("unexpected error {err}"),
+    }
+}
+
+#[test]
+fn test_parse_valid() {
+    for sddl in [
+        APPLICATION_SDDL,
+        "D:(A;;GA;;;WD)",
+        "O:DAG:DAD:PAI(A;OICI;FA;;;SY)(D;;0x2;;;AN)S:AI(AU;SAFA;FW;;;WD)",
+        "D:(OA;;RP;bf967aba-0de6-11d0-a285-00aa003049e2;;AU)",
+        "D:(XA;;FX;;;S-1-1-0;(Member_of {SID(BA)}))",
+        "O:S-1-5-21-1004336348-1177238915-682003330-512",
+    ] {
+        assert_eq!(sddl, SecurityDescriptor::parse(sddl).unwrap().as_sddl());
+    }
+}
+
+#[test]
+fn test_parse_reports_offset_of_typo() {
+    assert_eq!(0, offset_of(""));
+    assert_eq!(0, offset_of("X:BA"));
+    assert_eq!(2, offset_of("O:XX"));
+    assert_eq!(4, offset_of("O:BAO:BA"));
+    assert_eq!(3, offset_of("D:(Q;;0x1;;;SY)"));
+    assert_eq!(5, offset_of("D:(A;XX;0x1;;;SY)"));
+    assert_eq!(6, offset_of("D:(A;;0xZ;;;SY)"));
+    assert_eq!(11, offset_of("D:(A;;GA;;;S-1-5-X)"));
+    assert_eq!(2, offset_of("D:(A;;GA;;;SY"));
+    assert_eq!(2, offset_of("D:(A;;GA;SY)"));
+    assert_eq!(3, offset_of("S:(A;;GA;;;SY)"));
+    assert_eq!(9, offset_of("D:(A;;GA;not-a-guid;;SY)"));
+    assert_eq!(2, offset_of("D:QQ(A;;GA;;;SY)"));
+}
+
+#[test]
+fn test_builder() {
+    let descriptor = SecurityDescriptor::builder()
+        .read("BA")
+        .clear("BA")
+        .write("S-1-5-20")
+        .read("S-1-5-20")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        "O:BAG:SYD:(A;;0xf0007;;;SY)(A;;0x5;;;BA)(A;;0x3;;;S-1-5-20)",
+        descriptor.as_sddl()
+    );
+}
+
+#[test]
+fn test_builder_rejects_invalid_sid() {
+    let err = SecurityDescriptor::builder()
+        .read("Administrators")
+        .build()
+        .unwrap_err();
+
+    assert!(
+        err.to_string()
+            .contains("'Administrators' is neither a SID"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_custom_log_is_registered_with_descriptor() {
+    let registry = InMemoryRegistry::new();
+    let descriptor: SecurityDescriptor = "D:(A;;0x1;;;BA)".parse().unwrap();
+    let options = CustomLogOptions::new().security_descriptor(descriptor);
+    let source = LogSource::custom_with_options("Audit", None, options);
+
+    source.register_with(&registry).unwrap();
+
+    assert_eq!(
+        Some(RegistryValue::String("D:(A;;0x1;;;BA)".to_owned())),
+        registry
+            .get_value(
+                r"SYSTEM\CurrentControlSet\Services\EventLog\Audit",
+                "CustomSD"
+            )
+            .unwrap()
+    );
+    assert_eq!(
+        Some("D:(A;;0x1;;;BA)".to_owned()),
+        source
+            .status_with(&registry)
+            .unwrap()
+            .log_settings
+            .custom_sd
+    );
+}
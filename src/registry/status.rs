@@ -0,0 +1,158 @@
+use super::{REG_BASEKEY, RegistrationProblem, RegistryBackend, RegistryValue, expand_environment};
+use crate::{error::RegistryError, eventmsgs};
+use std::path::Path;
+
+/// A module listed in one of the message file values of a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageFileStatus {
+    /// The registry value listing the module, e.g. `EventMessageFile`.
+    pub value: &'static str,
+    pub path: String,
+    /// Whether the module exists once variables are expanded. `None` for sources on a remote
+    /// computer, whose files can't be checked.
+    pub exists: Option<bool>,
+}
+
+/// The settings stored on the key of a log.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogSettings {
+    pub max_size: Option<u32>,
+    pub retention: Option<u32>,
+    pub auto_backup: Option<bool>,
+    pub file: Option<String>,
+    /// The SDDL of the `CustomSD` value.
+    pub custom_sd: Option<String>,
+}
+
+/// What the registration of a source looks like, returned by
+/// [`EventLogRegistry::status`](super::EventLogRegistry::status).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistrationStatus {
+    /// The log containing the source, `None` if the source is not registered.
+    pub log: Option<String>,
+    pub message_files: Vec<MessageFileStatus>,
+    pub category_count: Option<u32>,
+    pub types_supported: Option<u32>,
+    pub log_settings: LogSettings,
+    pub problems: Vec<RegistrationProblem>,
+}
+
+const MESSAGE_FILE_VALUES: [&str; 3] = [
+    "EventMessageFile",
+    "CategoryMessageFile",
+    "ParameterMessageFile",
+];
+
+impl RegistrationStatus {
+    pub(crate) fn not_registered() -> Self {
+        Self {
+            problems: vec![RegistrationProblem::NotRegistered],
+            ..Self::default()
+        }
+    }
+
+    /// Whether the source is registered without problems.
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Reads the registration of `source`, which is expected in `expected_log`.
+    pub(crate) fn read(
+        backend: &impl RegistryBackend,
+        source: &str,
+        expected_log: &str,
+        check_files: bool,
+    ) -> Result<Self, RegistryError> {
+        let Some(log) = super::find_source_log(backend, source)? else {
+            return Ok(Self::not_registered());
+        };
+        let log_key = format!(r"{REG_BASEKEY}\{log}");
+        let source_key = format!(r"{log_key}\{source}");
+        let mut status = Self {
+            log: Some(log.clone()),
+            ..Self::default()
+        };
+
+        if !log.eq_ignore_ascii_case(expected_log) {
+            status.problems.push(RegistrationProblem::WrongLog {
+                log: log.clone(),
+                expected: expected_log.to_owned(),
+            });
+        }
+
+        for value in MESSAGE_FILE_VALUES {
+            let paths = match backend.get_value(&source_key, value)? {
+                Some(RegistryValue::String(paths) | RegistryValue::ExpandString(paths)) => paths,
+                _ => continue,
+            };
+            for path in paths.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+                let exists = check_files.then(|| Path::new(&expand_environment(path)).is_file());
+                if exists == Some(false) {
+                    status
+                        .problems
+                        .push(RegistrationProblem::MessageFileNotFound {
+                            log: log.clone(),
+                            path: path.to_owned(),
+                        });
+                }
+                status.message_files.push(MessageFileStatus {
+                    value,
+                    path: path.to_owned(),
+                    exists,
+                });
+            }
+        }
+        if !status
+            .message_files
+            .iter()
+            .any(|file| file.value == "EventMessageFile")
+        {
+            status
+                .problems
+                .push(RegistrationProblem::MissingMessageFile { log: log.clone() });
+        }
+
+        status.category_count = get_u32(backend, &source_key, "CategoryCount")?;
+        if status.category_count != Some(eventmsgs::CATEGORY_COUNT) {
+            status
+                .problems
+                .push(RegistrationProblem::CategoryCountMismatch {
+                    log: log.clone(),
+                    expected: eventmsgs::CATEGORY_COUNT,
+                    actual: status.category_count,
+                });
+        }
+        status.types_supported = get_u32(backend, &source_key, "TypesSupported")?;
+
+        status.log_settings = LogSettings {
+            max_size: get_u32(backend, &log_key, "MaxSize")?,
+            retention: get_u32(backend, &log_key, "Retention")?,
+            auto_backup: get_u32(backend, &log_key, "AutoBackupLogFiles")?.map(|v| v != 0),
+            file: match backend.get_value(&log_key, "File")? {
+                Some(RegistryValue::String(file) | RegistryValue::ExpandString(file)) => Some(file),
+                _ => None,
+            },
+            custom_sd: match backend.get_value(&log_key, "CustomSD")? {
+                Some(RegistryValue::String(sddl)) => Some(sddl),
+                _ => None,
+            },
+        };
+
+        Ok(status)
+    }
+}
+
+pub(super) fn get_u32(
+    backend: &impl RegistryBackend,
+    path: &str,
+    name: &str,
+) -> Result<Option<u32>, RegistryError> {
+    match backend.get_value(path, name)? {
+        Some(RegistryValue::U32(value)) => Ok(Some(value)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+#[path = "./status_test.rs"]
+mod status_test;
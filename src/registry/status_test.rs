@@ -0,0 +1,176 @@
+use super::*;
+use crate::{CustomLogOptions, EventLogRegistry, InMemoryRegistry, LogSource, Retention};
+
+const BASE_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog";
+
+fn exe_path() -> String {
+    crate::registry::current_exe_path().unwrap()
+}
+
+#[test]
+fn test_not_registered() {
+    let registry = InMemoryRegistry::new();
+
+    let status = LogSource::application("Test App")
+        .status_with(&registry)
+        .unwrap();
+
+    assert_eq!(RegistrationStatus::not_registered(), status);
+    assert!(!status.is_healthy());
+}
+
+#[test]
+fn test_registered_application() {
+    let registry = InMemoryRegistry::new();
+    let source = LogSource::application("Test App");
+    source.register_with(&registry).unwrap();
+
+    let status = source.status_with(&registry).unwrap();
+
+    assert_eq!(
+        RegistrationStatus {
+            log: Some("Application".to_owned()),
+            message_files: vec![
+                MessageFileStatus {
+                    value: "EventMessageFile",
+                    path: exe_path(),
+                    exists: Some(true),
+                },
+                MessageFileStatus {
+                    value: "CategoryMessageFile",
+                    path: exe_path(),
+                    exists: Some(true),
+                },
+            ],
+            category_count: Some(eventmsgs::CATEGORY_COUNT),
+            types_supported: Some(7),
+            log_settings: LogSettings::default(),
+            problems: vec![],
+        },
+        status
+    );
+    assert!(status.is_healthy());
+}
+
+#[test]
+fn test_custom_log_settings() {
+    let registry = InMemoryRegistry::new();
+    let options = CustomLogOptions::new()
+        .max_size(0x00100000)
+        .retention(Retention::Never)
+        .auto_backup(true)
+        .file(r"%SystemRoot%\Logs\Test.evtx");
+    let source = LogSource::custom_with_options("Test Log", None, options);
+    source.register_with(&registry).unwrap();
+
+    let status = source.status_with(&registry).unwrap();
+
+    assert_eq!(Some("Test Log".to_owned()), status.log);
+    assert_eq!(
+        LogSettings {
+            max_size: Some(0x00100000),
+            retention: Some(0xFFFFFFFF),
+            auto_backup: Some(true),
+            file: Some(r"%SystemRoot%\Logs\Test.evtx".to_owned()),
+            custom_sd: None,
+        },
+        status.log_settings
+    );
+    assert!(status.is_healthy());
+}
+
+#[test]
+fn test_detects_problems() {
+    let registry = InMemoryRegistry::new();
+    let key = format!(r"{BASE_KEY}\Other Log\Test App");
+    registry.create_key(&key).unwrap();
+    registry
+        .set_value(&key, "CategoryMessageFile", &r"C:\Missing\app.exe".into())
+        .unwrap();
+    registry
+        .set_value(&key, "CategoryCount", &RegistryValue::U32(1))
+        .unwrap();
+
+    let status = LogSource::application("Test App")
+        .status_with(&registry)
+        .unwrap();
+
+    let log = "Other Log".to_owned();
+    assert_eq!(
+        vec![
+            RegistrationProblem::WrongLog {
+                log: log.clone(),
+                expected: "Application".to_owned()
+            },
+            RegistrationProblem::MessageFileNotFound {
+                log: log.clone(),
+                path: r"C:\Missing\app.exe".to_owned()
+            },
+            RegistrationProblem::MissingMessageFile { log: log.clone() },
+            RegistrationProblem::CategoryCountMismatch {
+                log,
+                expected: eventmsgs::CATEGORY_COUNT,
+                actual: Some(1)
+            },
+        ],
+        status.problems
+    );
+    assert_eq!(None, status.types_supported);
+}
+
+#[test]
+fn test_custom_log_reports_its_source() {
+    let registry = InMemoryRegistry::new();
+    let source = LogSource::custom("Test Log", vec!["Updater"]);
+    source.register_with(&registry).unwrap();
+    registry
+        .delete_value(&format!(r"{BASE_KEY}\Test Log\Updater"), "EventMessageFile")
+        .unwrap();
+
+    let status = source.status_with(&registry).unwrap();
+
+    assert_eq!(
+        vec![RegistrationProblem::MissingMessageFile {
+            log: "Test Log".to_owned()
+        }],
+        status.problems
+    );
+    assert!(
+        source
+            .source_status_with(&registry, "Test Log")
+            .unwrap()
+            .is_healthy()
+    );
+}
+
+#[test]
+fn test_remote_files_are_not_checked() {
+    let registry = InMemoryRegistry::new();
+    let key = format!(r"{BASE_KEY}\Application\Test App");
+    registry.create_key(&key).unwrap();
+    registry
+        .set_value(&key, "EventMessageFile", &r"C:\Remote\app.exe".into())
+        .unwrap();
+    registry
+        .set_value(&key, "CategoryCount", &eventmsgs::CATEGORY_COUNT.into())
+        .unwrap();
+
+    let status = LogSource::application("Test App")
+        .with_server("server")
+        .status_with(&registry)
+        .unwrap();
+
+    assert_eq!(None, status.message_files[0].exists);
+    assert!(status.is_healthy());
+}
+
+#[test]
+fn test_status_does_not_write() {
+    let registry = InMemoryRegistry::new();
+
+    LogSource::custom("Test Log", None)
+        .status_with(&registry)
+        .unwrap();
+
+    assert!(registry.keys().is_empty());
+}
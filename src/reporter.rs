@@ -0,0 +1,106 @@
+use crate::{
+    error::Result,
+    sink::{EventRecord, EventSink},
+    stats::EventLogStats,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::Level;
+
+// ReportEventW rejects insertion strings longer than this many UTF-16 characters
+pub(crate) const MAX_MESSAGE_LEN: usize = 31_839;
+
+const TRUNCATED_SUFFIX: &str = "... [truncated]";
+
+/// Writes records to a sink, handling message size limits and keeping statistics.
+#[derive(Clone)]
+pub(crate) struct Reporter {
+    sink: Arc<dyn EventSink>,
+    stats: EventLogStats,
+    self_report: Option<(Duration, Arc<Mutex<Instant>>)>,
+}
+
+impl Reporter {
+    pub(crate) fn new(
+        sink: Arc<dyn EventSink>,
+        stats: EventLogStats,
+        self_report_interval: Option<Duration>,
+    ) -> Self {
+        Self {
+            sink,
+            stats,
+            self_report: self_report_interval
+                .map(|interval| (interval, Arc::new(Mutex::new(Instant::now())))),
+        }
+    }
+
+    #[cfg(feature = "log")]
+    pub(crate) fn with_stats(mut self, stats: EventLogStats) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub(crate) fn report(&self, record: EventRecord) {
+        let _ = self.write(record, |sink, record| sink.report(record));
+
+        if let Some((interval, last_report)) = &self.self_report {
+            let mut last_report = last_report.lock().unwrap();
+            if last_report.elapsed() >= *interval {
+                *last_report = Instant::now();
+                let message = format!("tracing-eventlog statistics: {}", self.stats.snapshot());
+                if let Err(e) = self.sink.report(&EventRecord::new(Level::INFO, 0, message)) {
+                    self.stats.record_failure(&e);
+                }
+            }
+        }
+    }
+
+    /// Writes `record` with [`EventSink::try_report`], without the periodic statistics event.
+    pub(crate) fn try_report(&self, record: EventRecord) -> Result<()> {
+        self.write(record, |sink, record| sink.try_report(record))
+    }
+
+    fn write(
+        &self,
+        mut record: EventRecord,
+        report: impl FnOnce(&dyn EventSink, &EventRecord) -> Result<()>,
+    ) -> Result<()> {
+        if truncate_message(&mut record.message) {
+            self.stats.record_truncated();
+        }
+        let bytes = record.message.encode_utf16().count() as u64 * 2;
+        let result = report(self.sink.as_ref(), &record);
+        match &result {
+            Ok(()) => self.stats.record_written(bytes),
+            Err(e) => self.stats.record_failure(e),
+        }
+        result
+    }
+}
+
+/// Shortens `message` to fit in a single insertion string. Returns whether it was truncated.
+pub(crate) fn truncate_message(message: &mut String) -> bool {
+    if message.len() <= MAX_MESSAGE_LEN || message.encode_utf16().count() <= MAX_MESSAGE_LEN {
+        return false;
+    }
+
+    let max_len = MAX_MESSAGE_LEN - TRUNCATED_SUFFIX.len();
+    let mut utf16_len = 0;
+    let end = message
+        .char_indices()
+        .find(|(_, c)| {
+            utf16_len += c.len_utf16();
+            utf16_len > max_len
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(message.len());
+    message.truncate(end);
+    message.push_str(TRUNCATED_SUFFIX);
+    true
+}
+
+#[cfg(test)]
+#[path = "./reporter_test.rs"]
+mod reporter_test;
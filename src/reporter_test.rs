@@ -0,0 +1,72 @@
+use super::*;
+use crate::{error::EventLogError, sink::MockEventSink};
+
+fn record(message: &str) -> EventRecord {
+    EventRecord::new(Level::INFO, 0, message)
+}
+
+#[test]
+fn test_counts_written_and_failed() {
+    let mut sink = MockEventSink::new();
+    let mut calls = 0;
+    sink.expect_report().returning(move |_| {
+        calls += 1;
+        if calls == 2 {
+            Err(EventLogError::Unavailable)
+        } else {
+            Ok(())
+        }
+    });
+    let stats = EventLogStats::new();
+    let reporter = Reporter::new(Arc::new(sink), stats.clone(), None);
+
+    reporter.report(record("abc"));
+    reporter.report(record("def"));
+    reporter.report(record("été"));
+
+    let snapshot = stats.snapshot();
+    assert_eq!(2, snapshot.written);
+    assert_eq!(12, snapshot.bytes_written);
+    assert_eq!(Some(&1), snapshot.failed.get("unavailable"));
+    assert_eq!(1, snapshot.failed_total());
+}
+
+#[test]
+fn test_truncates_long_messages() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| {
+            record.message.encode_utf16().count() == MAX_MESSAGE_LEN
+                && record.message.ends_with(TRUNCATED_SUFFIX)
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let stats = EventLogStats::new();
+    let reporter = Reporter::new(Arc::new(sink), stats.clone(), None);
+
+    reporter.report(record(&"x".repeat(MAX_MESSAGE_LEN + 1)));
+
+    let snapshot = stats.snapshot();
+    assert_eq!(1, snapshot.truncated);
+    assert_eq!(MAX_MESSAGE_LEN as u64 * 2, snapshot.bytes_written);
+}
+
+#[test]
+fn test_self_report() {
+    let mut sink = MockEventSink::new();
+    sink.expect_report()
+        .withf(|record| record.message == "event")
+        .returning(|_| Ok(()))
+        .once();
+    sink.expect_report()
+        .withf(|record| {
+            record.message
+                == "tracing-eventlog statistics: written=1 bytes_written=10 truncated=0 \
+                    dropped=0 (filtered=0 unavailable=0) failed=0"
+        })
+        .returning(|_| Ok(()))
+        .once();
+    let reporter = Reporter::new(Arc::new(sink), EventLogStats::new(), Some(Duration::ZERO));
+
+    reporter.report(record("event"));
+}
@@ -0,0 +1,67 @@
+use crate::{
+    error::{EventLogError, Result},
+    eventmsgs,
+};
+#[cfg(test)]
+use mockall::automock;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use tracing::Level;
+
+/// A formatted event ready to be written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventRecord {
+    pub level: Level,
+    pub event_id: u32,
+    pub category: u16,
+    pub message: String,
+    /// Binary data attached to the event, shown in the event's details.
+    pub raw_data: Vec<u8>,
+}
+
+impl EventRecord {
+    /// Creates a record using the message ID generated for `level`.
+    pub fn new(level: Level, category: u16, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            event_id: event_id(level),
+            category,
+            message: message.into(),
+            raw_data: Vec::new(),
+        }
+    }
+}
+
+pub(crate) fn event_id(level: Level) -> u32 {
+    match level {
+        Level::ERROR => eventmsgs::MSG_ERROR,
+        Level::WARN => eventmsgs::MSG_WARNING,
+        Level::INFO => eventmsgs::MSG_INFO,
+        Level::DEBUG => eventmsgs::MSG_DEBUG,
+        Level::TRACE => eventmsgs::MSG_TRACE,
+    }
+}
+
+/// Somewhere event records can be written to.
+#[cfg_attr(test, automock)]
+pub trait EventSink: Send + Sync {
+    fn report(&self, record: &EventRecord) -> Result<()>;
+
+    /// Like [`report`](Self::report), but fails with [`EventLogError::Busy`] instead of waiting
+    /// for a lock, which may be held by the code that is panicking.
+    fn try_report(&self, record: &EventRecord) -> Result<()> {
+        self.report(record)
+    }
+}
+
+/// Locks `mutex` for [`EventSink::try_report`] if it is free.
+pub(crate) fn try_lock<T: ?Sized>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Ok(guard),
+        // Whoever panicked while holding it won't use it again
+        Err(TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+        Err(TryLockError::WouldBlock) => Err(EventLogError::Busy),
+    }
+}
+
+/// Opens a new connection to a sink.
+pub(crate) type OpenSink = Box<dyn Fn() -> Result<Box<dyn EventSink>> + Send + Sync>;
@@ -0,0 +1,129 @@
+use crate::error::EventLogError;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Why an event was not written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropReason {
+    /// Below the maximum level of the layer or logger, or from a target denied by the layer's
+    /// [`FieldFilter`](crate::FieldFilter).
+    Filtered,
+    /// There is no event log on this platform and no fallback is configured.
+    Unavailable,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    written: AtomicU64,
+    filtered: AtomicU64,
+    unavailable: AtomicU64,
+    truncated: AtomicU64,
+    bytes_written: AtomicU64,
+    failed: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Counters describing what a layer or logger has written.
+///
+/// Cloning the handle shares the same counters, so a handle can be kept for exporting metrics
+/// after the layer has been moved into a subscriber.
+#[derive(Clone, Debug, Default)]
+pub struct EventLogStats {
+    counters: Arc<Counters>,
+}
+
+/// A point-in-time copy of [`EventLogStats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    pub written: u64,
+    pub filtered: u64,
+    pub unavailable: u64,
+    pub truncated: u64,
+    /// Size of the written messages, in UTF-16 bytes.
+    pub bytes_written: u64,
+    /// Failed reports keyed by [`EventLogError::kind`].
+    pub failed: BTreeMap<String, u64>,
+}
+
+impl StatsSnapshot {
+    pub fn dropped(&self) -> u64 {
+        self.filtered + self.unavailable
+    }
+
+    pub fn failed_total(&self) -> u64 {
+        self.failed.values().sum()
+    }
+}
+
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "written={} bytes_written={} truncated={} dropped={} (filtered={} unavailable={}) \
+            failed={}",
+            self.written,
+            self.bytes_written,
+            self.truncated,
+            self.dropped(),
+            self.filtered,
+            self.unavailable,
+            self.failed_total()
+        )?;
+        for (kind, count) in &self.failed {
+            write!(f, " failed[{kind}]={count}")?;
+        }
+        Ok(())
+    }
+}
+
+impl EventLogStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let counters = &self.counters;
+        StatsSnapshot {
+            written: counters.written.load(Ordering::Relaxed),
+            filtered: counters.filtered.load(Ordering::Relaxed),
+            unavailable: counters.unavailable.load(Ordering::Relaxed),
+            truncated: counters.truncated.load(Ordering::Relaxed),
+            bytes_written: counters.bytes_written.load(Ordering::Relaxed),
+            failed: counters.failed.lock().unwrap().clone(),
+        }
+    }
+
+    pub(crate) fn record_written(&self, bytes: u64) {
+        self.counters.written.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .bytes_written
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dropped(&self, reason: DropReason) {
+        let counter = match reason {
+            DropReason::Filtered => &self.counters.filtered,
+            DropReason::Unavailable => &self.counters.unavailable,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_truncated(&self) {
+        self.counters.truncated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_failure(&self, error: &EventLogError) {
+        *self
+            .counters
+            .failed
+            .lock()
+            .unwrap()
+            .entry(error.kind())
+            .or_default() += 1;
+    }
+}